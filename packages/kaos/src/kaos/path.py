@@ -1,12 +1,22 @@
 from __future__ import annotations
 
-from collections.abc import AsyncGenerator
+import re
+from collections.abc import AsyncGenerator, Iterable
 from pathlib import Path, PurePath
 from stat import S_ISDIR, S_ISREG
-from typing import Any, Literal
+from typing import TYPE_CHECKING, Any, Literal
 
 import kaos
 
+if TYPE_CHECKING:
+    from pydantic import GetCoreSchemaHandler
+    from pydantic_core import CoreSchema
+
+_EXTENDED_PREFIX = "\\\\?\\"
+_EXTENDED_UNC_PREFIX = "\\\\?\\UNC\\"
+# Two or more scheme characters, so a Windows drive such as `C:` is not taken for one.
+_URL_SCHEME = re.compile(r"^[A-Za-z][A-Za-z0-9+.-]+://")
+
 
 class KaosPath:
     """
@@ -16,6 +26,23 @@ class KaosPath:
     def __init__(self, *args: str) -> None:
         self._path: PurePath = kaos.pathclass()(*args)
 
+    @classmethod
+    def parse(cls, text: str) -> KaosPath:
+        """
+        Create a `KaosPath` from a string such as user input, for the current Kaos backend.
+
+        Raises:
+            ValueError: If `text` is empty, contains a NUL character, or is a URL such as
+                `file:///tmp`.
+        """
+        if not text:
+            raise ValueError("Path must not be empty")
+        if "\0" in text:
+            raise ValueError(f"Path {text!r} contains a NUL character")
+        if _URL_SCHEME.match(text):
+            raise ValueError(f"Path {text!r} is a URL, not a filesystem path")
+        return cls(text)
+
     @classmethod
     def unsafe_from_local_path(cls, path: Path) -> KaosPath:
         """
@@ -52,18 +79,77 @@ class KaosPath:
         return f"KaosPath({repr(str(self._path))})"
 
     def __str__(self) -> str:
+        """
+        Return the path in the backend's native form: native separators for a local path,
+        forward slashes for a remote one. `KaosPath(str(path))` parses it back.
+        """
         return str(self._path)
 
+    @classmethod
+    def __get_pydantic_core_schema__(
+        cls, source_type: Any, handler: GetCoreSchemaHandler
+    ) -> CoreSchema:
+        """
+        Let pydantic models hold a `KaosPath`, serialized to JSON as its string form.
+
+        Parsing the string back yields an equal path, keeping any Windows extended-length prefix.
+        pydantic is imported lazily, so kaos itself does not depend on it.
+        """
+        from pydantic_core import core_schema
+
+        from_str = core_schema.no_info_after_validator_function(cls.parse, core_schema.str_schema())
+        return core_schema.json_or_python_schema(
+            json_schema=from_str,
+            python_schema=core_schema.union_schema([core_schema.is_instance_schema(cls), from_str]),
+            serialization=core_schema.plain_serializer_function_ser_schema(str, when_used="json"),
+        )
+
+    def display_normalized(self) -> str:
+        """
+        Return the path as a forward-slash string without the Windows extended-length prefix.
+
+        This is only for display and stable comparisons across platforms; the path itself
+        is left unchanged.
+        """
+        text = str(self._path)
+        if text.startswith(_EXTENDED_UNC_PREFIX):
+            text = "\\\\" + text[len(_EXTENDED_UNC_PREFIX) :]
+        elif text.startswith(_EXTENDED_PREFIX):
+            text = text[len(_EXTENDED_PREFIX) :]
+        return text.replace("\\", "/")
+
     @property
     def name(self) -> str:
         """Return the final component of the path."""
         return self._path.name
 
+    @property
+    def extension(self) -> str | None:
+        """
+        Return the final component's extension without the dot, e.g. `md` for `SKILL.md`.
+
+        A name without a dot, or with only a leading one such as `.gitignore`, has None.
+        """
+        return self._path.suffix[1:] or None
+
     @property
     def parent(self) -> KaosPath:
         """Return the parent directory of the path."""
         return KaosPath(str(self._path.parent))
 
+    def parent_or_none(self) -> KaosPath | None:
+        """
+        Return the parent directory, or None once there is no component left to remove.
+
+        Unlike `parent`, which returns the path itself at an anchor such as `/`, this ends a
+        walk up the tree. A single-component relative path such as `skills` also returns None
+        rather than `.`.
+        """
+        parent = self._path.parent
+        if parent == self._path or not parent.parts:
+            return None
+        return KaosPath(str(parent))
+
     def is_absolute(self) -> bool:
         """Return True if the path is absolute."""
         return self._path.is_absolute()
@@ -72,6 +158,37 @@ class KaosPath:
         """Join this path with other path components."""
         return KaosPath(str(self._path.joinpath(*other)))
 
+    def join(self, components: Iterable[str]) -> KaosPath:
+        """
+        Join this path with each of `components` in turn, e.g. `base.join(["a", "b"])`.
+
+        A component containing separators, such as `a/b`, adds each of its parts.
+        """
+        return self.joinpath(*components)
+
+    def join_checked(self, relative: str) -> KaosPath:
+        """
+        Join a relative path onto this one, rejecting any result that escapes this path.
+
+        `.` and `..` components are resolved lexically, without touching the filesystem, so
+        symlinks inside this path are not followed.
+
+        Raises:
+            ValueError: If `relative` is absolute or climbs above this path.
+        """
+        other = kaos.pathclass()(relative)
+        if other.anchor:
+            raise ValueError(f'Path "{relative}" must be relative')
+        parts: list[str] = []
+        for part in other.parts:
+            if part == "..":
+                if not parts:
+                    raise ValueError(f'Path "{relative}" escapes "{self}"')
+                parts.pop()
+            elif part != ".":
+                parts.append(part)
+        return self.joinpath(*parts)
+
     def __truediv__(self, other: str | KaosPath) -> KaosPath:
         """Join this path with another path using the `/` operator."""
         p = other._path if isinstance(other, KaosPath) else other
@@ -92,6 +209,16 @@ class KaosPath:
         # So we follow the pathlib behavior which doesn't preserve trailing slashes
         return normalized
 
+    def normalize(self) -> KaosPath:
+        """
+        Collapse `.` and `..` components lexically, e.g. `a/./b/../c` becomes `a/c`.
+
+        The filesystem is not touched, so this works for paths that do not exist. Relative
+        paths stay relative and keep any leading `..`; a path that collapses entirely
+        becomes `.`.
+        """
+        return kaos.normpath(self)
+
     def relative_to(self, other: KaosPath) -> KaosPath:
         """Return the relative path from `other` to this path."""
         relative_path = self._path.relative_to(other._path)
@@ -123,7 +250,12 @@ class KaosPath:
         return await kaos.stat(self, follow_symlinks=follow_symlinks)
 
     async def exists(self, *, follow_symlinks: bool = True) -> bool:
-        """Return True if the path points to an existing filesystem entry."""
+        """
+        Return True if the path points to an existing filesystem entry.
+
+        Errors while checking, such as `PermissionError` on an unreadable parent, mean the
+        entry cannot be confirmed and return False instead of raising.
+        """
         try:
             await self.stat(follow_symlinks=follow_symlinks)
             return True
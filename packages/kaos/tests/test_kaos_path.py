@@ -34,6 +34,83 @@ def test_join_and_parent(kaos_cwd: KaosPath):
     assert not child.is_absolute()
 
 
+def test_parent_or_none(kaos_cwd: KaosPath):
+    nested = kaos_cwd / "skills" / "alpha"
+    root = KaosPath(kaos_cwd.unsafe_to_local_path().anchor)
+
+    assert nested.parent_or_none() == kaos_cwd / "skills"
+    assert root.parent_or_none() is None
+    assert KaosPath("skills", "alpha").parent_or_none() == KaosPath("skills")
+    assert KaosPath("skills").parent_or_none() is None
+
+
+def test_name_and_extension(kaos_cwd: KaosPath):
+    skill_md = KaosPath("skills", "alpha", "SKILL.md")
+
+    assert skill_md.name == "SKILL.md"
+    assert skill_md.extension == "md"
+    assert KaosPath("archive.tar.gz").extension == "gz"
+    assert KaosPath("skills", ".skillignore").extension is None
+    assert KaosPath("skills", "alpha").extension is None
+
+
+def test_join(kaos_cwd: KaosPath):
+    base = KaosPath("base")
+
+    assert base.join(["a", "b", "c"]) == base / "a" / "b" / "c"
+    assert base.join(iter(["a/b", "c"])) == base / "a" / "b" / "c"
+    assert base.join([]) == base
+
+
+def test_join_checked(kaos_cwd: KaosPath):
+    base = KaosPath("skills", "alpha")
+
+    assert base.join_checked("docs/./guide/../intro.md") == base / "docs" / "intro.md"
+    assert base.join_checked("docs/..") == base
+    with pytest.raises(ValueError, match="escapes"):
+        base.join_checked("docs/../../../etc/passwd")
+    with pytest.raises(ValueError, match="must be relative"):
+        base.join_checked(str(kaos_cwd / "etc"))
+
+
+def test_normalize(kaos_cwd: KaosPath):
+    assert KaosPath("a/./b/../c").normalize() == KaosPath("a/c")
+    assert KaosPath("../a/../../b").normalize() == KaosPath("../../b")
+    assert KaosPath("a/..").normalize() == KaosPath(".")
+    assert (kaos_cwd / "x" / ".." / "y").normalize() == kaos_cwd / "y"
+    assert not KaosPath("missing/./file").normalize().is_absolute()
+
+
+def test_parse(kaos_cwd: KaosPath):
+    assert KaosPath.parse("skills/alpha") == KaosPath("skills", "alpha")
+    with pytest.raises(ValueError, match="NUL"):
+        KaosPath.parse("skills/\0alpha")
+    with pytest.raises(ValueError, match="empty"):
+        KaosPath.parse("")
+    with pytest.raises(ValueError, match="URL"):
+        KaosPath.parse("file:///tmp/skills")
+    with pytest.raises(ValueError, match="URL"):
+        KaosPath.parse("s3://bucket/skills")
+    assert KaosPath.parse("C:/skills") == KaosPath("C:/skills")
+
+
+def test_str_is_native_and_parseable(kaos_cwd: KaosPath):
+    path = KaosPath("skills", "alpha")
+
+    assert f"{path}" == str(Path("skills") / "alpha")
+    assert KaosPath(f"{path}") == path
+
+
+def test_display_normalized(kaos_cwd: KaosPath):
+    assert KaosPath("skills/alpha").display_normalized() == "skills/alpha"
+    assert KaosPath("C:\\skills/mixed\\alpha").display_normalized() == "C:/skills/mixed/alpha"
+    assert KaosPath("\\\\?\\C:\\skills\\alpha").display_normalized() == "C:/skills/alpha"
+    assert (
+        KaosPath("\\\\?\\UNC\\server\\share\\alpha").display_normalized()
+        == "//server/share/alpha"
+    )
+
+
 def test_home_and_cwd(kaos_cwd: KaosPath):
     assert str(KaosPath.home()) == str(Path.home())
     assert str(KaosPath.cwd()) == str(kaos_cwd)
@@ -73,6 +150,19 @@ async def test_exists_and_file_ops(kaos_cwd: KaosPath):
     assert await dir_path.is_dir()
 
 
+async def test_exists_treats_errors_as_missing(
+    kaos_cwd: KaosPath, monkeypatch: pytest.MonkeyPatch
+):
+    assert await kaos_cwd.exists()
+    assert not await (kaos_cwd / "missing").exists()
+
+    async def deny(self: KaosPath, follow_symlinks: bool = True) -> None:
+        raise PermissionError(f"Permission denied: {self}")
+
+    monkeypatch.setattr(KaosPath, "stat", deny)
+    assert not await kaos_cwd.exists()
+
+
 async def test_iterdir_and_glob_from_kaos_path(kaos_cwd: KaosPath):
     base_dir = KaosPath("data")
     await base_dir.mkdir()
@@ -1,5 +1,10 @@
 from __future__ import annotations
 
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from kaos.path import KaosPath
+
 
 class KimiCLIException(Exception):
     """Base exception class for Kimi Code CLI."""
@@ -41,3 +46,50 @@ class MCPRuntimeError(KimiCLIException, RuntimeError):
     """MCP runtime error."""
 
     pass
+
+
+class SkillError(KimiCLIException):
+    """Skill loading error."""
+
+    def __init__(self, message: str, *, path: KaosPath | None = None) -> None:
+        super().__init__(f"{path}: {message}" if path is not None else message)
+        self.path = path
+
+
+class SkillIOError(SkillError):
+    """A skill file or directory could not be read."""
+
+    pass
+
+
+class SkillParseError(SkillError, ValueError):
+    """SKILL.md frontmatter or metadata is invalid."""
+
+    pass
+
+
+class SkillFlowError(SkillError, ValueError):
+    """The flow diagram of a flow skill could not be parsed."""
+
+    pass
+
+
+class SkillNotFoundError(SkillError):
+    """A skill directory has no definition file."""
+
+    pass
+
+
+class SkillNameCollisionError(SkillError, ValueError):
+    """Two skills in the same root claim the same name."""
+
+    def __init__(self, name: str, *, path: KaosPath, other: KaosPath) -> None:
+        super().__init__(f'Skill name "{name}" is also used by {other}', path=path)
+        self.name = name
+        self.other = other
+
+
+class SkillDependencyError(SkillError, ValueError):
+    """Skill `depends_on` declarations reference missing skills or form a cycle."""
+
+    pass
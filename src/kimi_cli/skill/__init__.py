@@ -2,22 +2,112 @@
 
 from __future__ import annotations
 
-from collections.abc import Callable, Iterable, Iterator
+import asyncio
+import difflib
+import hashlib
+import heapq
+import io
+import json
+import posixpath
+import re
+import tarfile
+import textwrap
+import time
+from collections.abc import AsyncGenerator, Callable, Iterable, Iterator
+from dataclasses import asdict, dataclass, field, replace
+from datetime import UTC, datetime
 from pathlib import Path
-from typing import Literal
+from typing import Any, Literal, Protocol, cast, runtime_checkable
 
 from kaos import get_current_kaos
 from kaos.local import local_kaos
 from kaos.path import KaosPath
 from loguru import logger
-from pydantic import BaseModel, ConfigDict
+from pydantic import BaseModel, ConfigDict, Field, ValidationError
 
-from kimi_cli.skill.flow import Flow, FlowError
+from kimi_cli.exception import (
+    SkillDependencyError,
+    SkillFlowError,
+    SkillIOError,
+    SkillNotFoundError,
+    SkillParseError,
+)
+from kimi_cli.skill.flow import Flow, FlowError, FlowNodePolicy, lint_flow
 from kimi_cli.skill.flow.d2 import parse_d2_flowchart
-from kimi_cli.skill.flow.mermaid import parse_mermaid_flowchart
-from kimi_cli.utils.frontmatter import parse_frontmatter
+from kimi_cli.skill.flow.mermaid import parse_mermaid_diagram
+from kimi_cli.utils.envvar import get_env_bool
+from kimi_cli.utils.frontmatter import (
+    render_frontmatter,
+    split_frontmatter,
+    split_frontmatter_lenient,
+)
 
 SkillType = Literal["standard", "flow"]
+MergeMode = Literal["replace", "extend"]
+FlowLang = Literal["mermaid", "d2"]
+DiagnosticLevel = Literal["info", "warning", "error"]
+SkillFormat = Literal["kimi", "claude", "codex"]
+
+TEMPERATURE_RANGE = (0.0, 2.0)
+SKILLIGNORE_FILE = ".skillignore"
+SKILL_CATEGORY_FILE = ".skillcategory"
+"""Marker file that makes a directory without a definition file a category of nested skills."""
+FRONTMATTER_SCHEMA_VERSION = 2
+"""Frontmatter schema assumed when `schema_version` is omitted."""
+
+_EDITOR_TEMP_SUFFIXES = ("~", ".swp", ".tmp")
+_MAX_CATEGORY_DEPTH = 8
+_FLOW_PARSERS: dict[str, Callable[[str], Flow]] = {
+    "mermaid": parse_mermaid_diagram,
+    "d2": parse_d2_flowchart,
+}
+
+
+@dataclass(frozen=True, slots=True, kw_only=True)
+class DiscoveryOptions:
+    """Options controlling how skill directories are scanned."""
+
+    include_hidden: bool = False
+    """Also load skills from directories whose names start with `.`."""
+    strict_frontmatter: bool = False
+    """Reject skills whose frontmatter contains unknown keys instead of ignoring them."""
+    allow_override_protected: bool = False
+    """Let later roots shadow skills marked `protected: true`."""
+    lenient_frontmatter: bool = False
+    """Recover the valid keys of invalid YAML frontmatter instead of skipping the skill."""
+    warn_unknown_keys: bool = False
+    """Report unknown frontmatter keys as diagnostics, suggesting the closest known key."""
+    definition_filenames: tuple[str, ...] = ("SKILL.md",)
+    """File names accepted as a skill's definition, e.g. `skill.md`, in order of preference."""
+    token_budget: int | None = None
+    """Warn about skills whose `estimated_tokens` exceed this many tokens."""
+
+
+class DiscoveryObserver:
+    """
+    Hooks called while a skill root is scanned, e.g. to gather metrics or drive a progress bar.
+
+    Every hook does nothing by default; subclasses override the ones they need.
+    """
+
+    def root_started(self, root: KaosPath) -> None:
+        """Called before `root` is scanned."""
+
+    def skill_parsed(self, skill: Skill) -> None:
+        """Called for each valid skill, before it is yielded."""
+
+    def dir_skipped(self, path: KaosPath) -> None:
+        """Called for a directory left out: hidden, ignored, or holding an invalid skill."""
+
+    def root_finished(
+        self, root: KaosPath, *, elapsed: float, scanned: int, skipped: int
+    ) -> None:
+        """
+        Called once `root` is done, including when discovery stopped early.
+
+        `elapsed` is in seconds; `scanned` counts the directories looked at, `skipped` those
+        among them reported to `dir_skipped`.
+        """
 
 
 def get_builtin_skills_dir() -> Path:
@@ -27,17 +117,57 @@ def get_builtin_skills_dir() -> Path:
     return Path(__file__).parent.parent / "skills"
 
 
-def get_user_skills_dir_candidates() -> tuple[KaosPath, ...]:
+_USER_SKILLS_DIR_CANDIDATES: list[tuple[int, str]] = [
+    (500, ".config/agents/skills"),
+    (400, ".agents/skills"),
+    (300, ".kimi/skills"),
+    (200, ".claude/skills"),
+    (100, ".codex/skills"),
+]
+"""`(priority, path relative to home)` pairs; higher priorities are checked first."""
+
+
+def register_user_skills_candidate(relative: str, priority: int) -> Callable[[], None]:
+    """
+    Add a user-level skills directory candidate, e.g. `.mytool/skills` under the home dir.
+
+    Candidates are checked in descending `priority`; the built-in ones use 500
+    (`.config/agents/skills`) down to 100 (`.codex/skills`) in steps of 100. On a tie, the
+    candidate registered earlier wins. Registering a path that is already a candidate is
+    ignored.
+
+    Returns:
+        A callable that removes the candidate again; it does nothing for an ignored
+        registration or once called.
+
+    Raises:
+        ValueError: If `relative` is absolute or escapes the home directory.
+    """
+    # Any base works for validation and comparison, since only `relative` is checked.
+    base = KaosPath("home")
+    target = base.join_checked(relative)
+    if any(base.join_checked(existing) == target for _, existing in _USER_SKILLS_DIR_CANDIDATES):
+        return lambda: None
+    candidate = (priority, relative)
+    _USER_SKILLS_DIR_CANDIDATES.append(candidate)
+
+    def unregister() -> None:
+        if candidate in _USER_SKILLS_DIR_CANDIDATES:
+            _USER_SKILLS_DIR_CANDIDATES.remove(candidate)
+
+    return unregister
+
+
+def get_user_skills_dir_candidates(home_dir: KaosPath | None = None) -> tuple[KaosPath, ...]:
     """
     Get user-level skills directory candidates in priority order.
+
+    Candidates are relative to `home_dir`, which defaults to the current user's home
+    directory as reported by the environment.
     """
-    return (
-        KaosPath.home() / ".config" / "agents" / "skills",
-        KaosPath.home() / ".agents" / "skills",
-        KaosPath.home() / ".kimi" / "skills",
-        KaosPath.home() / ".claude" / "skills",
-        KaosPath.home() / ".codex" / "skills",
-    )
+    home = home_dir if home_dir is not None else KaosPath.home()
+    ordered = sorted(_USER_SKILLS_DIR_CANDIDATES, key=lambda candidate: -candidate[0])
+    return tuple(home.join_checked(relative) for _, relative in ordered)
 
 
 def get_project_skills_dir_candidates(work_dir: KaosPath) -> tuple[KaosPath, ...]:
@@ -68,11 +198,11 @@ async def find_first_existing_dir(candidates: Iterable[KaosPath]) -> KaosPath |
     return None
 
 
-async def find_user_skills_dir() -> KaosPath | None:
+async def find_user_skills_dir(home_dir: KaosPath | None = None) -> KaosPath | None:
     """
-    Return the first existing user-level skills directory.
+    Return the first existing user-level skills directory; see `get_user_skills_dir_candidates`.
     """
-    return await find_first_existing_dir(get_user_skills_dir_candidates())
+    return await find_first_existing_dir(get_user_skills_dir_candidates(home_dir))
 
 
 async def find_project_skills_dir(work_dir: KaosPath) -> KaosPath | None:
@@ -86,20 +216,29 @@ async def resolve_skills_roots(
     work_dir: KaosPath,
     *,
     skills_dir_override: KaosPath | None = None,
+    home_dir: KaosPath | None = None,
+    include_builtins: bool = True,
 ) -> list[KaosPath]:
     """
     Resolve layered skill roots in priority order.
 
-    Built-in skills load first when supported by the active KAOS backend. When an
-    override is provided, user/project discovery is skipped.
+    Built-in skills load first when supported by the active KAOS backend, unless
+    `include_builtins` is False or `KIMI_DISABLE_BUILTIN_SKILLS` is set. When an
+    override is provided, user/project discovery is skipped. User skills are looked up
+    under `home_dir`, by default the home directory from the environment; passing it
+    explicitly keeps resolution independent of process-wide state.
     """
     roots: list[KaosPath] = []
-    if _supports_builtin_skills():
+    if (
+        include_builtins
+        and not get_env_bool("KIMI_DISABLE_BUILTIN_SKILLS")
+        and _supports_builtin_skills()
+    ):
         roots.append(KaosPath.unsafe_from_local_path(get_builtin_skills_dir()))
     if skills_dir_override is not None:
         roots.append(skills_dir_override)
         return roots
-    if user_dir := await find_user_skills_dir():
+    if user_dir := await find_user_skills_dir(home_dir):
         roots.append(user_dir)
     if project_dir := await find_project_skills_dir(work_dir):
         roots.append(project_dir)
@@ -116,17 +255,365 @@ def index_skills(skills: Iterable[Skill]) -> dict[str, Skill]:
     return {normalize_skill_name(skill.name): skill for skill in skills}
 
 
-async def discover_skills_from_roots(skills_dirs: Iterable[KaosPath]) -> list[Skill]:
+def find_skill_by_name(skills: Iterable[Skill], name: str) -> Skill | None:
     """
-    Discover skills from multiple directory roots.
+    Find a skill by its name or one of its `aliases`, ignoring case.
+
+    A skill whose primary name matches wins over a skill that only has it as an alias.
     """
+    key = normalize_skill_name(name)
+    alias_match: Skill | None = None
+    for skill in skills:
+        if normalize_skill_name(skill.name) == key:
+            return skill
+        if alias_match is None and any(normalize_skill_name(a) == key for a in skill.aliases):
+            alias_match = skill
+    return alias_match
+
+
+@dataclass(slots=True)
+class SkillDiff:
+    """Differences between two skill sets, each bucket sorted by name."""
+
+    added: list[Skill] = field(default_factory=list)
+    removed: list[Skill] = field(default_factory=list)
+    changed: list[Skill] = field(default_factory=list)
+    """The new versions of skills whose content changed."""
+
+    def is_empty(self) -> bool:
+        return not (self.added or self.removed or self.changed)
+
+    def summary(self) -> str:
+        return (
+            f"added {len(self.added)}, changed {len(self.changed)}, removed {len(self.removed)}"
+        )
+
+
+def sort_by_recency(skills: list[Skill]) -> None:
+    """
+    Sort skills in place by `modified`, most recent first and then by name.
+
+    Skills without a modification time come last.
+    """
+    skills.sort(key=lambda s: s.name)
+    # The sort is stable, so ties stay in name order even though it is reversed.
+    skills.sort(key=lambda s: s.modified or datetime.min.replace(tzinfo=UTC), reverse=True)
+
+
+def group_by_category(skills: Iterable[Skill]) -> dict[str, list[Skill]]:
+    """
+    Group skills by `category`, with categories and the skills in each sorted by name.
+
+    Skills without a category are grouped under `""`.
+    """
+    groups: dict[str, list[Skill]] = {}
+    for skill in skills:
+        groups.setdefault(skill.category or "", []).append(skill)
+    return {
+        category: sorted(groups[category], key=lambda s: s.name) for category in sorted(groups)
+    }
+
+
+def diff_skills(old: Iterable[Skill], new: Iterable[Skill]) -> SkillDiff:
+    """Compare two skill sets by normalized name, e.g. before and after a reload."""
+    old_by_name = index_skills(old)
+    new_by_name = index_skills(new)
+    diff = SkillDiff()
+    for key in sorted(old_by_name.keys() | new_by_name.keys()):
+        before = old_by_name.get(key)
+        after = new_by_name.get(key)
+        if before is None and after is not None:
+            diff.added.append(after)
+        elif after is None and before is not None:
+            diff.removed.append(before)
+        elif before is not None and after is not None and not before.same_content(after):
+            diff.changed.append(after)
+    return diff
+
+
+@runtime_checkable
+class SkillSource(Protocol):
+    """A layer of skills, e.g. a skills directory or a database-backed registry."""
+
+    async def list_skills(self) -> list[Skill]:
+        """Return every skill in this source; invalid ones are left out."""
+        ...
+
+
+@dataclass(slots=True)
+class FsSkillSource:
+    """The skills found by scanning a directory with `discover_skills`."""
+
+    skills_dir: KaosPath
+    options: DiscoveryOptions | None = None
+    cancel_event: asyncio.Event | None = None
+    observer: DiscoveryObserver | None = None
+
+    async def list_skills(self) -> list[Skill]:
+        return await discover_skills(
+            self.skills_dir,
+            options=self.options,
+            cancel_event=self.cancel_event,
+            observer=self.observer,
+        )
+
+
+async def discover_skills_from_roots(
+    skills_dirs: Iterable[KaosPath | SkillSource],
+    *,
+    options: DiscoveryOptions | None = None,
+    cancel_event: asyncio.Event | None = None,
+    observer: DiscoveryObserver | None = None,
+) -> list[Skill]:
+    """
+    Discover skills from multiple roots, each a directory or any other `SkillSource`.
+
+    Later roots shadow earlier ones, except that a `protected` skill is kept (with a diagnostic)
+    unless `options.allow_override_protected` is set. References to other skills, via
+    `replaced_by` or `@skill:<name>` flow node markers, are checked against the final set.
+
+    Directories are scanned as an `FsSkillSource` with the given `options`, `cancel_event`
+    and `observer`; other sources use their own settings. Once `cancel_event` is set, the
+    scan stops and the skills found so far are returned.
+    """
+    options = options or DiscoveryOptions()
     skills_by_name: dict[str, Skill] = {}
-    for skills_dir in skills_dirs:
-        for skill in await discover_skills(skills_dir):
-            skills_by_name[normalize_skill_name(skill.name)] = skill
+    for source in skills_dirs:
+        if cancel_event is not None and cancel_event.is_set():
+            break
+        if isinstance(source, KaosPath):
+            source = FsSkillSource(
+                source, options=options, cancel_event=cancel_event, observer=observer
+            )
+        for skill in await source.list_skills():
+            key = normalize_skill_name(skill.name)
+            base = skills_by_name.get(key)
+            if base is not None and base.protected and not options.allow_override_protected:
+                diagnostic = SkillDiagnostic(
+                    message=f"Ignoring override from {skill.dir}: skill is protected"
+                )
+                base.diagnostics.append(diagnostic)
+                _log_diagnostic(base, diagnostic)
+                continue
+            if base is not None and skill.merge_mode == "extend":
+                skill = _extend_skill(base, skill)
+            skills_by_name[key] = skill
+    for skill in skills_by_name.values():
+        replaced_by = skill.deprecation.replaced_by if skill.deprecation else None
+        if replaced_by is not None and normalize_skill_name(replaced_by) not in skills_by_name:
+            diagnostic = SkillDiagnostic(
+                message=f'`replaced_by` names unknown skill "{replaced_by}"'
+            )
+            skill.diagnostics.append(diagnostic)
+            _log_diagnostic(skill, diagnostic)
+        for node in skill.flow.nodes.values() if skill.flow else ():
+            invokes = node.invokes
+            if invokes is not None and normalize_skill_name(invokes) not in skills_by_name:
+                diagnostic = SkillDiagnostic(
+                    message=f'Flow node "{node.id}" invokes unknown skill "{invokes}"'
+                )
+                skill.diagnostics.append(diagnostic)
+                _log_diagnostic(skill, diagnostic)
     return sorted(skills_by_name.values(), key=lambda s: s.name)
 
 
+class DiscoveryReportSkill(BaseModel):
+    """One discovered skill as recorded in a `DiscoveryReport`."""
+
+    id: str
+    """Normalized skill name, the key skills shadow each other by."""
+    name: str
+    type: SkillType
+    root: str
+    """The root the skill was loaded from."""
+    diagnostics: list[SkillDiagnostic] = Field(default_factory=list)
+
+
+class DiscoveryReport(BaseModel):
+    """Machine-readable summary of a multi-root discovery, e.g. for CI golden files."""
+
+    roots: list[str] = Field(default_factory=list)
+    """Every root scanned, in shadowing order."""
+    skills: list[DiscoveryReportSkill] = Field(default_factory=list)
+    """The skills left after shadowing, sorted by name."""
+
+
+async def build_discovery_report(
+    skills_dirs: Iterable[KaosPath],
+    *,
+    options: DiscoveryOptions | None = None,
+) -> DiscoveryReport:
+    """Run `discover_skills_from_roots` and record which root each skill came from."""
+    skills_dirs = list(skills_dirs)
+    skills = await discover_skills_from_roots(skills_dirs, options=options)
+    return DiscoveryReport(
+        roots=[root.display_normalized() for root in skills_dirs],
+        skills=[
+            DiscoveryReportSkill(
+                id=normalize_skill_name(skill.name),
+                name=skill.name,
+                type=skill.type,
+                root=_source_root(skill, skills_dirs).display_normalized(),
+                diagnostics=skill.diagnostics,
+            )
+            for skill in skills
+        ],
+    )
+
+
+def _source_root(skill: Skill, skills_dirs: list[KaosPath]) -> KaosPath:
+    """Return the root `skill` was found under, falling back to its parent directory."""
+    for root in reversed(skills_dirs):
+        try:
+            skill.dir.relative_to(root)
+        except ValueError:
+            continue
+        return root
+    return skill.dir.parent
+
+
+async def write_discovery_report(
+    skills_dirs: Iterable[KaosPath],
+    output: KaosPath,
+    *,
+    options: DiscoveryOptions | None = None,
+) -> DiscoveryReport:
+    """
+    Discover skills from `skills_dirs` and write the report to `output` as indented JSON.
+
+    Raises:
+        SkillIOError: If the report cannot be written.
+    """
+    report = await build_discovery_report(skills_dirs, options=options)
+    try:
+        await output.write_text(report.model_dump_json(indent=2) + "\n", encoding="utf-8")
+    except OSError as exc:
+        raise SkillIOError(f"Failed to write discovery report: {exc}", path=output) from exc
+    return report
+
+
+def _extend_skill(base: Skill, extension: Skill) -> Skill:
+    """Append an extending skill's body to the skill it shadows and union their tags."""
+    body = "\n\n".join(part for part in (base.body, extension.body) if part)
+    tags = list(dict.fromkeys([*base.tags, *extension.tags]))
+    return base.model_copy(update={"body": body, "tags": tags})
+
+
+async def discover_applicable_skills(
+    skills_dirs: Iterable[KaosPath],
+    work_dir: KaosPath,
+    *,
+    options: DiscoveryOptions | None = None,
+    cancel_event: asyncio.Event | None = None,
+    observer: DiscoveryObserver | None = None,
+) -> list[Skill]:
+    """
+    Discover skills from multiple roots, keeping only those whose `requires` are met.
+    """
+    skills = await discover_skills_from_roots(
+        skills_dirs, options=options, cancel_event=cancel_event, observer=observer
+    )
+    return [
+        skill for skill in skills if skill.requires is None or await skill.requires.is_met(work_dir)
+    ]
+
+
+def order_skills(skills: Iterable[Skill]) -> list[Skill]:
+    """
+    Order skills so that every skill comes after the skills it `depends_on`.
+
+    Ties are broken by name, so the result is deterministic.
+
+    Raises:
+        SkillDependencyError: If a dependency is missing or the dependencies form a cycle.
+    """
+    by_name = index_skills(skills)
+    dependents: dict[str, list[str]] = {key: [] for key in by_name}
+    deps_of: dict[str, list[str]] = {}
+    pending: dict[str, int] = {}
+    for key, skill in by_name.items():
+        deps = deps_of[key] = list(dict.fromkeys(map(normalize_skill_name, skill.depends_on)))
+        for dep in deps:
+            if dep not in by_name:
+                raise SkillDependencyError(
+                    f'Skill "{skill.name}" depends on unknown skill "{dep}"', path=skill.dir
+                )
+            dependents[dep].append(key)
+        pending[key] = len(deps)
+
+    ready = [key for key, count in pending.items() if count == 0]
+    heapq.heapify(ready)
+    ordered: list[Skill] = []
+    while ready:
+        key = heapq.heappop(ready)
+        ordered.append(by_name[key])
+        for dependent in dependents[key]:
+            pending[dependent] -= 1
+            if pending[dependent] == 0:
+                heapq.heappush(ready, dependent)
+
+    if len(ordered) < len(by_name):
+        # Every skill left over still waits on another leftover one, so walking those
+        # dependencies must revisit a skill.
+        remaining = {key for key, count in pending.items() if count > 0}
+        key = min(remaining)
+        path: list[str] = []
+        while key not in path:
+            path.append(key)
+            key = next(dep for dep in deps_of[key] if dep in remaining)
+        cycle = [by_name[k].name for k in [*path[path.index(key) :], key]]
+        raise SkillDependencyError(f"Skill dependency cycle: {' -> '.join(cycle)}")
+    return ordered
+
+
+def resolve_inheritance(skills: Iterable[Skill]) -> list[Skill]:
+    """
+    Fill in the `tags`, `allowed_tools` and `model_hint` a skill leaves unset from the skill
+    it `extends`.
+
+    Chains resolve transitively, and whatever a child sets itself always wins, including an
+    empty `tags` or `allowed_tools` list: those count as unset only when not given at all.
+    Skills are returned in their original order; those that inherit something are copies.
+
+    Raises:
+        SkillDependencyError: If a parent is missing or skills extend each other in a cycle.
+    """
+    skills = list(skills)
+    by_name = index_skills(skills)
+    resolved: dict[str, Skill] = {}
+
+    def resolve(skill: Skill, chain: list[str]) -> Skill:
+        key = normalize_skill_name(skill.name)
+        if key in resolved:
+            return resolved[key]
+        if key in chain:
+            cycle = [by_name[k].name for k in [*chain[chain.index(key) :], key]]
+            raise SkillDependencyError(f"Skill inheritance cycle: {' -> '.join(cycle)}")
+        if skill.extends is None:
+            resolved[key] = skill
+            return skill
+        parent = by_name.get(normalize_skill_name(skill.extends))
+        if parent is None:
+            raise SkillDependencyError(
+                f'Skill "{skill.name}" extends unknown skill "{skill.extends}"', path=skill.dir
+            )
+        parent = resolve(parent, [*chain, key])
+        resolved[key] = skill.model_copy(
+            update={
+                "tags": skill.tags if "tags" in skill.model_fields_set else parent.tags,
+                "allowed_tools": (
+                    skill.allowed_tools
+                    if "allowed_tools" in skill.model_fields_set
+                    else parent.allowed_tools
+                ),
+                "model_hint": skill.model_hint or parent.model_hint,
+            }
+        )
+        return resolved[key]
+
+    return [resolve(skill, []) for skill in skills]
+
+
 async def read_skill_text(skill: Skill) -> str | None:
     """Read the SKILL.md contents for a skill."""
     try:
@@ -140,6 +627,87 @@ async def read_skill_text(skill: Skill) -> str | None:
         return None
 
 
+class SkillDiagnostic(BaseModel):
+    """A non-fatal problem found while loading a skill."""
+
+    level: DiagnosticLevel = "warning"
+    message: str
+
+
+class SkillRequirement(BaseModel):
+    """
+    A predicate on the work dir that must hold for a skill to be applicable.
+
+    All set conditions must hold; `any_of` holds if at least one nested requirement does.
+    """
+
+    model_config = ConfigDict(extra="forbid")
+
+    file_exists: str | None = None
+    """Path relative to the work dir that must be an existing file."""
+    dir_exists: str | None = None
+    """Path relative to the work dir that must be an existing directory."""
+    any_of: list[SkillRequirement] = Field(default_factory=list)
+
+    async def is_met(self, work_dir: KaosPath) -> bool:
+        if self.file_exists is not None and not await (work_dir / self.file_exists).is_file():
+            return False
+        if self.dir_exists is not None and not await (work_dir / self.dir_exists).is_dir():
+            return False
+        if self.any_of:
+            for requirement in self.any_of:
+                if await requirement.is_met(work_dir):
+                    break
+            else:
+                return False
+        return True
+
+
+class SkillDeprecation(BaseModel):
+    """Marks a skill as deprecated; it still loads but warns on discovery."""
+
+    replaced_by: str | None = None
+    """Name of the skill that supersedes this one."""
+
+
+class SkillFrontmatter(BaseModel):
+    """Typed view of the keys recognized in SKILL.md frontmatter."""
+
+    model_config = ConfigDict(extra="ignore", strict=True)
+
+    schema_version: int | None = None
+    name: str | None = None
+    description: str | None = None
+    description_i18n: dict[str, str] = Field(default_factory=dict)
+    type: str | None = None
+    license: str | None = None
+    compatibility: str | None = None
+    metadata: dict[str, Any] = Field(default_factory=dict)
+    model: str | None = None
+    """Preferred model for the skill, surfaced as `Skill.model_hint`."""
+    temperature: float | None = None
+    tags: list[str] = Field(default_factory=list)
+    aliases: list[str] = Field(default_factory=list)
+    allowed_tools: list[str] = Field(default_factory=list)
+    resources: list[str] = Field(default_factory=list)
+    merge_mode: MergeMode = "replace"
+    requires: SkillRequirement | None = None
+    deprecated: bool = False
+    replaced_by: str | None = None
+    protected: bool = False
+    depends_on: list[str] = Field(default_factory=list)
+    category: str | None = None
+    extends: str | None = None
+    node_policies: dict[Any, Any] | None = None
+    """Raw per-node policies; validated against the parsed flow."""
+    nodes: dict[Any, Any] | None = None
+    """Raw per-node metadata; attached to the matching flow nodes."""
+
+
+class _StrictSkillFrontmatter(SkillFrontmatter):
+    model_config = ConfigDict(extra="forbid", strict=True)
+
+
 class Skill(BaseModel):
     """Information about a single skill."""
 
@@ -147,92 +715,947 @@ class Skill(BaseModel):
 
     name: str
     description: str
+    description_i18n: dict[str, str] = Field(default_factory=dict)
+    """Descriptions keyed by language tag; `description` is the fallback."""
     type: SkillType = "standard"
     dir: KaosPath
     flow: Flow | None = None
+    body: str = ""
+    """SKILL.md content after the frontmatter."""
+    tags: list[str] = Field(default_factory=list)
+    aliases: list[str] = Field(default_factory=list)
+    """Alternate names the skill can be invoked by; see `find_skill_by_name`."""
+    allowed_tools: list[str] = Field(default_factory=list)
+    """Tools the skill expects to use, e.g. `Bash(git:*)`; recorded but not enforced."""
+    resources: list[KaosPath] = Field(default_factory=list)
+    """Sidecar files declared in frontmatter, resolved relative to `dir`."""
+    merge_mode: MergeMode = "replace"
+    """How this skill combines with a same-named skill from an earlier root."""
+    requires: SkillRequirement | None = None
+    """Work dir predicate for `discover_applicable_skills`; `None` means always applicable."""
+    model_hint: str | None = None
+    """Preferred model for this skill; the runtime may choose to honor it."""
+    temperature: float | None = None
+    """Preferred sampling temperature, clamped into `TEMPERATURE_RANGE`."""
+    deprecation: SkillDeprecation | None = None
+    protected: bool = False
+    """Whether later skill roots are prevented from shadowing this skill."""
+    depends_on: list[str] = Field(default_factory=list)
+    """Names of skills that must be loaded before this one; see `order_skills`."""
+    category: str | None = None
+    """Grouping such as `git`, from the directories between the root and the skill."""
+    extends: str | None = None
+    """Name of the skill to inherit unset fields from; see `resolve_inheritance`."""
+    definition_file: str = "SKILL.md"
+    """Name of the file within `dir` the skill was defined in."""
+    modified: datetime | None = None
+    """When the definition file was last modified, set by directory discovery; else None."""
+    diagnostics: list[SkillDiagnostic] = Field(default_factory=list)
 
     @property
     def skill_md_file(self) -> KaosPath:
-        """Path to the SKILL.md file."""
-        return self.dir / "SKILL.md"
+        """Path to the definition file, usually SKILL.md."""
+        return self.dir / self.definition_file
+
+    def localized_description(self, lang: str) -> str:
+        """Return the description for `lang` (e.g. `ja` or `ja-JP`), or the default one."""
+        for key in (lang, lang.replace("_", "-").split("-")[0]):
+            if description := self.description_i18n.get(key):
+                return description
+        return self.description
+
+    def resource(self, name: str) -> KaosPath | None:
+        """Look up a declared resource by its declared relative path or its file name."""
+        try:
+            target = self.dir.join_checked(name)
+        except ValueError:
+            return None
+        for resource in self.resources:
+            if resource == target:
+                return resource
+        for resource in self.resources:
+            if resource.name == name:
+                return resource
+        return None
+
+    def render_terminal(self, width: int, *, color: bool = True) -> str:
+        """
+        Render the skill for a terminal `width` columns wide, e.g. for `kimi skills show`.
+
+        Flow skills list their steps in execution order, with decision branches indented under
+        their node. With `color` off the output is plain text.
+        """
+
+        def style(text: str, code: str) -> str:
+            return f"\x1b[{code}m{text}\x1b[0m" if color else text
 
+        def wrap(text: str, indent: str, first_indent: str | None = None) -> list[str]:
+            first_indent = indent if first_indent is None else first_indent
+            lines = textwrap.wrap(
+                text,
+                width=max(width, len(first_indent) + 1, len(indent) + 1),
+                initial_indent=first_indent,
+                subsequent_indent=indent,
+            )
+            return lines or [first_indent.rstrip()]
+
+        title = style(self.name, "1")
+        if self.type != "standard":
+            title += " " + style(f"[{self.type}]", "2")
+        lines = [title, *wrap(self.description, "  ")]
+        if self.tags:
+            lines += wrap("Tags: " + ", ".join(self.tags), "  ")
+        if self.flow is None:
+            return "\n".join(lines)
+
+        lines.append(style("Steps:", "1"))
+        step_no = 0
+        for step in self.flow.traverse():
+            if step.node.kind in ("begin", "end"):
+                continue
+            step_no += 1
+            prefix = f"  {step_no}. "
+            indent = " " * len(prefix)
+            label_lines = wrap(step.node.label_text, indent, prefix)
+            if step.node.kind == "decision":
+                label_lines = [
+                    line[: len(indent)] + style(line[len(indent) :], "33") for line in label_lines
+                ]
+            lines += label_lines
+            for branch, target in step.branches:
+                target_text = self.flow.nodes[target].label_text
+                branch_lines = wrap(f"{branch} -> {target_text}", indent + "  ")
+                head, at = branch_lines[0], len(indent) + 2
+                if head[at:].startswith(branch):
+                    branch_lines[0] = head[:at] + style(branch, "36") + head[at + len(branch) :]
+                lines += branch_lines
+        return "\n".join(lines)
+
+    def same_content(self, other: Skill) -> bool:
+        """Return True if both skills have the same content, wherever they were loaded from."""
+        return all(
+            getattr(self, name) == getattr(other, name)
+            for name in type(self).model_fields
+            if name not in _NON_CONTENT_FIELDS
+        )
 
-async def discover_skills(skills_dir: KaosPath) -> list[Skill]:
+    def content_hash(self) -> str:
+        """
+        Return a stable SHA-256 hex digest of the skill's content.
+
+        Like `same_content`, it ignores where the skill was loaded from: resources are hashed
+        relative to `dir`, so identical skills hash the same across machines and platforms.
+        """
+        payload = self.model_dump(
+            mode="json", exclude={*_NON_CONTENT_FIELDS, "flow", "resources"}
+        )
+        payload["resources"] = [
+            resource.relative_to(self.dir).display_normalized() for resource in self.resources
+        ]
+        if self.flow is not None:
+            payload["flow"] = {
+                **self.flow.to_json(),
+                "direction": self.flow.direction,
+                "node_policies": {
+                    node_id: asdict(policy) for node_id, policy in self.flow.node_policies.items()
+                },
+                "node_metadata": {
+                    node.id: node.metadata for node in self.flow.nodes.values() if node.metadata
+                },
+            }
+        encoded = json.dumps(
+            payload, sort_keys=True, separators=(",", ":"), ensure_ascii=False, default=str
+        )
+        return hashlib.sha256(encoded.encode("utf-8")).hexdigest()
+
+    def prompt_fragment(self) -> str:
+        """
+        Render the skill as a canonical block for inclusion in a system prompt.
+
+        Every frontend should use this so skills are presented to the model identically.
+        """
+        lines = [f"## Skill: {self.name}", "", self.description]
+        if self.body:
+            lines += ["", self.body]
+        if self.flow is not None:
+            lines += ["", "### Flow steps", *_render_flow_steps(self.flow)]
+        return "\n".join(lines)
+
+    def estimated_tokens(self) -> int:
+        """Roughly estimate the tokens `prompt_fragment` takes, at four characters per token."""
+        return -(-len(self.prompt_fragment()) // 4)
+
+    def fits_budget(self, max_tokens: int) -> bool:
+        """Return True if the skill's `estimated_tokens` are within `max_tokens`."""
+        return self.estimated_tokens() <= max_tokens
+
+
+_NON_CONTENT_FIELDS = frozenset({"dir", "definition_file", "modified", "diagnostics"})
+
+
+def _render_flow_steps(flow: Flow) -> list[str]:
+    lines: list[str] = []
+    for node in flow.nodes.values():
+        line = f"- {node.id} ({node.kind}): {node.label_text}"
+        targets = [
+            f"[{edge.label}] {edge.dst}" if edge.label else edge.dst
+            for edge in flow.outgoing.get(node.id, [])
+        ]
+        if targets:
+            line += " -> " + ", ".join(targets)
+        lines.append(line)
+    return lines
+
+
+async def discover_skills(
+    skills_dir: KaosPath,
+    *,
+    options: DiscoveryOptions | None = None,
+    cancel_event: asyncio.Event | None = None,
+    observer: DiscoveryObserver | None = None,
+) -> list[Skill]:
     """
     Discover all skills in the given directory.
 
+    A directory without a `SKILL.md` is skipped, unless it holds a `.skillcategory` file:
+    then it is a category searched for nested skills, e.g. `git/commit/SKILL.md` gets the
+    category `git`. Other definition file names can be accepted with
+    `options.definition_filenames`; when a directory has several, the first in that order is
+    used and the skill gets a diagnostic. Hidden directories and editor temp directories
+    (`foo~`, `foo.swp`, `foo.tmp`) are skipped unless `options.include_hidden` allows
+    dot-named ones. So are directories matching a glob pattern in the root's `.skillignore`
+    file.
+
     Args:
         skills_dir: Kaos path to the directory containing skills.
+        options: Discovery options; defaults are used when omitted.
+        cancel_event: Checked before each directory entry; once set, the scan stops and the
+            skills found so far are returned.
+        observer: Notified as the scan progresses; see `DiscoveryObserver`.
+
+    Two skills claiming the same alias is an error: the alias is dropped from the skill
+    that sorts later, which gets an error diagnostic.
 
     Returns:
         List of Skill objects, one for each valid skill found.
     """
-    if not await skills_dir.is_dir():
-        return []
+    skills = [
+        skill
+        async for skill in discover_skills_stream(
+            skills_dir, options=options, cancel_event=cancel_event, observer=observer
+        )
+    ]
+    skills.sort(key=lambda s: s.name)
+    _drop_colliding_aliases(skills)
+    return skills
 
-    skills: list[Skill] = []
 
-    async for skill_dir in skills_dir.iterdir():
-        if not await skill_dir.is_dir():
-            continue
+def _drop_colliding_aliases(skills: list[Skill]) -> None:
+    claimed: dict[str, Skill] = {}
+    for skill in skills:
+        kept: list[str] = []
+        for alias in skill.aliases:
+            owner = claimed.setdefault(normalize_skill_name(alias), skill)
+            if owner is skill:
+                kept.append(alias)
+                continue
+            diagnostic = SkillDiagnostic(
+                level="error",
+                message=f'Alias "{alias}" is also claimed by skill "{owner.name}"; ignoring it',
+            )
+            skill.diagnostics.append(diagnostic)
+            _log_diagnostic(skill, diagnostic)
+        skill.aliases = kept
 
-        skill_md = skill_dir / "SKILL.md"
-        if not await skill_md.is_file():
-            continue
 
-        try:
-            content = await skill_md.read_text(encoding="utf-8")
-            skills.append(parse_skill_text(content, dir_path=skill_dir))
-        except Exception as exc:
-            logger.info("Skipping invalid skill at {}: {}", skill_md, exc)
-            continue
+async def discover_skills_stream(
+    skills_dir: KaosPath,
+    *,
+    options: DiscoveryOptions | None = None,
+    cancel_event: asyncio.Event | None = None,
+    observer: DiscoveryObserver | None = None,
+) -> AsyncGenerator[Skill]:
+    """
+    Yield the skills in the given directory as soon as each one is parsed.
+
+    Works like `discover_skills`, including skipping invalid skills, but in directory order
+    rather than sorted by name. `observer` is told about the scan as it progresses.
+    """
+    options = options or DiscoveryOptions()
+    observer = observer or DiscoveryObserver()
+    observer.root_started(skills_dir)
+    started = time.perf_counter()
+    scanned = skipped = 0
+    try:
+        if not await skills_dir.is_dir():
+            return
+
+        ignore = await _read_skillignore(skills_dir)
+        pending: list[tuple[KaosPath, tuple[str, ...]]] = [(skills_dir, ())]
+
+        while pending:
+            parent, category = pending.pop()
+            async for skill_dir in parent.iterdir():
+                if cancel_event is not None and cancel_event.is_set():
+                    logger.debug("Skill discovery in {} cancelled", skills_dir)
+                    pending.clear()
+                    break
+                if not await skill_dir.is_dir():
+                    continue
+                scanned += 1
+                if _should_skip_dir(skill_dir.name, options) or _is_ignored(
+                    "/".join((*category, skill_dir.name)), ignore
+                ):
+                    skipped += 1
+                    observer.dir_skipped(skill_dir)
+                    continue
+
+                file_names = await _find_definition_files(skill_dir, options)
+                if not file_names:
+                    if len(category) < _MAX_CATEGORY_DEPTH and await (
+                        skill_dir / SKILL_CATEGORY_FILE
+                    ).is_file():
+                        pending.append((skill_dir, (*category, skill_dir.name)))
+                    continue
+
+                skill_md = skill_dir / file_names[0]
+                try:
+                    content = await skill_md.read_text(encoding="utf-8")
+                    mtime = (await skill_md.stat()).st_mtime
+                    skill = parse_skill_text(
+                        content,
+                        dir_path=skill_dir,
+                        strict=options.strict_frontmatter,
+                        lenient=options.lenient_frontmatter,
+                        warn_unknown_keys=options.warn_unknown_keys,
+                        category="/".join(category) or None,
+                        file_name=file_names[0],
+                    )
+                except Exception as exc:
+                    logger.info("Skipping invalid skill at {}: {}", skill_md, exc)
+                    skipped += 1
+                    observer.dir_skipped(skill_dir)
+                    continue
+                skill.modified = datetime.fromtimestamp(mtime, tz=UTC)
+                if len(file_names) > 1:
+                    skill.diagnostics.append(_ambiguous_definition_diagnostic(file_names))
+
+                for resource in skill.resources:
+                    if not await resource.exists():
+                        message = f"Declared resource {resource} does not exist"
+                        skill.diagnostics.append(SkillDiagnostic(message=message))
+                if options.token_budget is not None and not skill.fits_budget(
+                    options.token_budget
+                ):
+                    message = (
+                        f"Skill takes about {skill.estimated_tokens()} tokens, "
+                        f"over the budget of {options.token_budget}"
+                    )
+                    skill.diagnostics.append(SkillDiagnostic(message=message))
+                _log_diagnostics(skill)
+                observer.skill_parsed(skill)
+                yield skill
+    finally:
+        observer.root_finished(
+            skills_dir, elapsed=time.perf_counter() - started, scanned=scanned, skipped=skipped
+        )
+
+
+async def _find_definition_files(skill_dir: KaosPath, options: DiscoveryOptions) -> list[str]:
+    """Return the definition files present in `skill_dir`, in order of preference."""
+    if options.definition_filenames == ("SKILL.md",):
+        # Skip listing the directory in the common case.
+        return ["SKILL.md"] if await (skill_dir / "SKILL.md").is_file() else []
+    # Match names exactly, so `skill.md` and `SKILL.md` stay distinct on case-insensitive
+    # file systems.
+    present = {entry.name async for entry in skill_dir.iterdir()}
+    return [
+        name
+        for name in options.definition_filenames
+        if name in present and await (skill_dir / name).is_file()
+    ]
+
+
+def _ambiguous_definition_diagnostic(file_names: list[str]) -> SkillDiagnostic:
+    return SkillDiagnostic(
+        message=f"Found several definition files ({', '.join(file_names)}); "
+        f"using {file_names[0]}"
+    )
+
+
+async def discover_skills_from_archive(
+    archive: KaosPath,
+    *,
+    options: DiscoveryOptions | None = None,
+) -> list[Skill]:
+    """
+    Discover skills packaged in a gzip tarball without extracting it.
+
+    Every `<dir>/SKILL.md` member becomes a skill whose `dir` is `archive / <dir>`, or a
+    member named after another of `options.definition_filenames`, as in `discover_skills`.
+    Those directories only exist inside the archive, so `read_skill_text` cannot read them
+    back.
+
+    Raises:
+        SkillIOError: If the archive cannot be read or is not a gzip tarball.
+    """
+    options = options or DiscoveryOptions()
+    try:
+        data = await archive.read_bytes()
+    except OSError as exc:
+        raise SkillIOError(f"Failed to read skill archive: {exc}", path=archive) from exc
+
+    skills: list[Skill] = []
+    try:
+        with tarfile.open(fileobj=io.BytesIO(data), mode="r:gz") as tar:
+            definitions: dict[str, dict[str, tarfile.TarInfo]] = {}
+            for member in tar.getmembers():
+                parent, file_name = posixpath.split(member.name.strip("/"))
+                if not member.isfile() or file_name not in options.definition_filenames:
+                    continue
+                if not parent:
+                    continue
+                if any(_should_skip_dir(part, options) for part in parent.split("/")):
+                    continue
+                definitions.setdefault(parent, {})[file_name] = member
+            for parent, members in definitions.items():
+                file_names = [name for name in options.definition_filenames if name in members]
+                extracted = tar.extractfile(members[file_names[0]])
+                if extracted is None:
+                    continue
+                skill_dir = archive.joinpath(*parent.split("/"))
+                try:
+                    content = extracted.read().decode("utf-8")
+                    skill = parse_skill_text(
+                        content,
+                        dir_path=skill_dir,
+                        strict=options.strict_frontmatter,
+                        lenient=options.lenient_frontmatter,
+                        warn_unknown_keys=options.warn_unknown_keys,
+                        category=posixpath.dirname(parent) or None,
+                        file_name=file_names[0],
+                    )
+                except Exception as exc:
+                    logger.info("Skipping invalid skill at {}: {}", skill_dir, exc)
+                    continue
+                if len(file_names) > 1:
+                    skill.diagnostics.append(_ambiguous_definition_diagnostic(file_names))
+                _log_diagnostics(skill)
+                skills.append(skill)
+    except tarfile.TarError as exc:
+        raise SkillIOError(f"Invalid skill archive: {exc}", path=archive) from exc
 
     return sorted(skills, key=lambda s: s.name)
 
 
-def parse_skill_text(content: str, *, dir_path: KaosPath) -> Skill:
+async def import_claude_skill(dir_path: KaosPath) -> Skill:
+    """
+    Load a skill written in the Claude Code format, mapping its frontmatter onto ours.
+
+    Hyphenated keys are renamed to their underscored form, so `allowed-tools` becomes
+    `allowed_tools`, split on commas when given as a string. `model: inherit` means no
+    model hint. Keys with no counterpart here are ignored.
+
+    Raises:
+        SkillNotFoundError: If the directory has no SKILL.md.
+        SkillIOError: If SKILL.md cannot be read.
+        SkillParseError: If the frontmatter is invalid.
+    """
+    skill_md = dir_path / "SKILL.md"
+    if not await skill_md.is_file():
+        raise SkillNotFoundError("No SKILL.md found", path=dir_path)
+    try:
+        content = await skill_md.read_text(encoding="utf-8")
+    except OSError as exc:
+        raise SkillIOError(f"Failed to read skill file: {exc}", path=skill_md) from exc
+    try:
+        parsed, body = split_frontmatter(content)
+    except ValueError as exc:
+        raise SkillParseError(str(exc), path=skill_md) from exc
+
+    data = {str(key).replace("-", "_"): value for key, value in (parsed or {}).items()}
+    if isinstance(tools := data.get("allowed_tools"), str):
+        data["allowed_tools"] = [tool.strip() for tool in tools.split(",") if tool.strip()]
+    if data.get("model") == "inherit":
+        del data["model"]
+    skill = _skill_from_frontmatter(
+        data,
+        body,
+        content,
+        dir_path=dir_path,
+        file_name="SKILL.md",
+        strict=False,
+        warn_unknown_keys=False,
+        category=None,
+        diagnostics=[],
+    )
+    _log_diagnostics(skill)
+    return skill
+
+
+_EXPORT_KEYS: dict[SkillFormat, dict[str, str]] = {
+    "claude": {
+        "name": "name",
+        "description": "description",
+        "allowed_tools": "allowed-tools",
+        "model": "model",
+    },
+    "codex": {"name": "name", "description": "description"},
+}
+"""The keys each foreign format supports, mapped to the name that format uses for them."""
+
+
+async def export_skill(
+    skill: Skill, target: SkillFormat, out_dir: KaosPath
+) -> list[SkillDiagnostic]:
+    """
+    Write `skill` as `out_dir/SKILL.md` in the frontmatter conventions of `target`.
+
+    Fields the target does not support are dropped, each with a returned diagnostic; the
+    body is written as is. Exporting as `kimi` keeps every field, so discovering the result
+    yields the same skill.
+
+    Raises:
+        SkillIOError: If the directory or file cannot be written.
+    """
+    data = _skill_frontmatter(skill)
+    diagnostics: list[SkillDiagnostic] = []
+    if target != "kimi":
+        keys = _EXPORT_KEYS[target]
+        diagnostics.extend(
+            SkillDiagnostic(message=f"Dropping `{key}`: not supported by the {target} format")
+            for key in data
+            if key not in keys
+        )
+        data = {keys[key]: value for key, value in data.items() if key in keys}
+        if target == "claude" and "allowed-tools" in data:
+            data["allowed-tools"] = ", ".join(data["allowed-tools"])
+    skill_md = out_dir / "SKILL.md"
+    try:
+        await out_dir.mkdir(parents=True, exist_ok=True)
+        await skill_md.write_text(render_frontmatter(data, skill.body), encoding="utf-8")
+    except OSError as exc:
+        raise SkillIOError(f"Failed to export skill: {exc}", path=skill_md) from exc
+    return diagnostics
+
+
+def _skill_frontmatter(skill: Skill) -> dict[str, Any]:
+    """Our frontmatter for `skill`, leaving out fields at their defaults."""
+    data: dict[str, Any] = {"name": skill.name, "description": skill.description}
+    optional: dict[str, Any] = {
+        "description_i18n": skill.description_i18n,
+        "type": skill.type if skill.type != "standard" else None,
+        "tags": skill.tags,
+        "aliases": skill.aliases,
+        "allowed_tools": skill.allowed_tools,
+        "model": skill.model_hint,
+        "temperature": skill.temperature,
+        "resources": [
+            resource.relative_to(skill.dir).display_normalized() for resource in skill.resources
+        ],
+        "merge_mode": skill.merge_mode if skill.merge_mode != "replace" else None,
+        "requires": skill.requires.model_dump(exclude_defaults=True) if skill.requires else None,
+        "deprecated": skill.deprecation is not None,
+        "replaced_by": skill.deprecation.replaced_by if skill.deprecation else None,
+        "protected": skill.protected,
+        "depends_on": skill.depends_on,
+        "category": skill.category,
+        "extends": skill.extends,
+    }
+    if skill.flow is not None:
+        optional["node_policies"] = {
+            node_id: {
+                key: value
+                for key, value in asdict(policy).items()
+                if value != getattr(FlowNodePolicy(), key)
+            }
+            for node_id, policy in skill.flow.node_policies.items()
+        }
+        optional["nodes"] = {
+            node.id: node.metadata for node in skill.flow.nodes.values() if node.metadata
+        }
+    data.update((key, value) for key, value in optional.items() if value)
+    return data
+
+
+def _log_diagnostics(skill: Skill) -> None:
+    for diagnostic in skill.diagnostics:
+        _log_diagnostic(skill, diagnostic)
+
+
+def _log_diagnostic(skill: Skill, diagnostic: SkillDiagnostic) -> None:
+    logger.log(
+        diagnostic.level.upper(),
+        "Skill {name}: {message}",
+        name=skill.name,
+        message=diagnostic.message,
+    )
+
+
+async def _read_skillignore(skills_dir: KaosPath) -> list[re.Pattern[str]]:
+    ignore_file = skills_dir / SKILLIGNORE_FILE
+    if not await ignore_file.is_file():
+        return []
+    try:
+        content = await ignore_file.read_text(encoding="utf-8")
+    except OSError as exc:
+        logger.warning("Failed to read {path}: {error}", path=ignore_file, error=exc)
+        return []
+    return [
+        _compile_ignore_pattern(line.strip())
+        for line in content.splitlines()
+        if line.strip() and not line.strip().startswith("#")
+    ]
+
+
+def _compile_ignore_pattern(pattern: str) -> re.Pattern[str]:
+    """Translate a `.skillignore` glob, where `*` stays within a segment and `**` spans them."""
+    parts: list[str] = []
+    for token in re.split(r"(\*\*/?|\*|\?)", pattern.strip("/")):
+        if token == "**/":
+            parts.append("(?:.*/)?")
+        elif token == "**":
+            parts.append(".*")
+        elif token == "*":
+            parts.append("[^/]*")
+        elif token == "?":
+            parts.append("[^/]")
+        else:
+            parts.append(re.escape(token))
+    return re.compile("".join(parts))
+
+
+def _is_ignored(relative_path: str, patterns: list[re.Pattern[str]]) -> bool:
+    segments = relative_path.split("/")
+    prefixes = ["/".join(segments[: idx + 1]) for idx in range(len(segments))]
+    return any(pattern.fullmatch(prefix) for pattern in patterns for prefix in prefixes)
+
+
+def _should_skip_dir(name: str, options: DiscoveryOptions) -> bool:
+    if name.endswith(_EDITOR_TEMP_SUFFIXES):
+        return True
+    return name.startswith(".") and not options.include_hidden
+
+
+def parse_skill_text(
+    content: str,
+    *,
+    dir_path: KaosPath,
+    strict: bool = False,
+    lenient: bool = False,
+    warn_unknown_keys: bool = False,
+    category: str | None = None,
+    file_name: str = "SKILL.md",
+) -> Skill:
     """
     Parse SKILL.md contents to extract name and description.
+
+    `category` is the one derived from the skill's location; a `category` frontmatter key
+    takes precedence over it. `file_name` is the name of the file within `dir_path` the
+    contents were read from.
+
+    Frontmatter values of the wrong type are dropped with a diagnostic. Unknown keys are
+    ignored unless `strict` is set, in which case they raise `SkillParseError`, or
+    `warn_unknown_keys`, which reports each with a diagnostic instead. Invalid YAML
+    raises `SkillParseError` too, unless `lenient` is set: then the keys that parse on their
+    own are kept and the rest are dropped with a diagnostic.
     """
-    frontmatter = parse_frontmatter(content) or {}
+    skill_md = dir_path / file_name
+    diagnostics: list[SkillDiagnostic] = []
+    try:
+        if lenient:
+            parsed, body, broken = split_frontmatter_lenient(content)
+            diagnostics.extend(
+                SkillDiagnostic(message=f"Ignoring `{key}`: invalid frontmatter YAML")
+                for key in broken
+            )
+        else:
+            parsed, body = split_frontmatter(content)
+    except ValueError as exc:
+        raise SkillParseError(str(exc), path=skill_md) from exc
+    return _skill_from_frontmatter(
+        parsed or {},
+        body,
+        content,
+        dir_path=dir_path,
+        file_name=file_name,
+        strict=strict,
+        warn_unknown_keys=warn_unknown_keys,
+        category=category,
+        diagnostics=diagnostics,
+    )
 
-    name = frontmatter.get("name") or dir_path.name
-    description = frontmatter.get("description") or "No description provided."
-    skill_type = frontmatter.get("type") or "standard"
+
+def _skill_from_frontmatter(
+    parsed: dict[str, Any],
+    body: str,
+    content: str,
+    *,
+    dir_path: KaosPath,
+    file_name: str,
+    strict: bool,
+    warn_unknown_keys: bool,
+    category: str | None,
+    diagnostics: list[SkillDiagnostic],
+) -> Skill:
+    """Build a skill from split SKILL.md `content`; see `parse_skill_text`."""
+    skill_md = dir_path / file_name
+    data = _migrate_frontmatter(parsed, diagnostics)
+    if warn_unknown_keys and not strict:
+        diagnostics.extend(_unknown_key_diagnostics(data))
+    frontmatter = _load_frontmatter(data, strict=strict, path=skill_md, diagnostics=diagnostics)
+
+    name = frontmatter.name or dir_path.name
+    description = frontmatter.description or "No description provided."
+    skill_type = frontmatter.type or "standard"
     if skill_type not in ("standard", "flow"):
-        raise ValueError(f'Invalid skill type "{skill_type}"')
+        raise SkillParseError(f'Invalid skill type "{skill_type}"', path=skill_md)
     flow = None
     if skill_type == "flow":
         try:
-            flow = _parse_flow_from_skill(content)
-        except ValueError as exc:
-            logger.error("Failed to parse flow skill {name}: {error}", name=name, error=exc)
+            flow = _parse_flow_from_skill(content, path=skill_md)
+        except SkillFlowError as exc:
+            diagnostics.append(
+                SkillDiagnostic(
+                    level="error",
+                    message=f"{exc.__cause__ or exc}; loaded as a standard skill",
+                )
+            )
             skill_type = "standard"
             flow = None
+    if flow is not None:
+        diagnostics.extend(
+            SkillDiagnostic(
+                message=f"Line {d.line_no}: {d.message}" if d.line_no is not None else d.message
+            )
+            for d in lint_flow(flow)
+        )
+        flow.node_policies = _parse_node_policies(frontmatter.node_policies, flow, diagnostics)
+        _attach_node_metadata(frontmatter.nodes, flow, diagnostics)
+    deprecation = None
+    if frontmatter.deprecated:
+        deprecation = SkillDeprecation(replaced_by=frontmatter.replaced_by)
+        hint = f'; use "{frontmatter.replaced_by}" instead' if frontmatter.replaced_by else ""
+        diagnostics.append(SkillDiagnostic(message=f"Skill is deprecated{hint}"))
+    # Only pass the lists the frontmatter sets, so an explicit `[]` still overrides a parent's.
+    inheritable = {
+        key: getattr(frontmatter, key)
+        for key in ("tags", "allowed_tools")
+        if key in frontmatter.model_fields_set
+    }
 
     return Skill(
         name=name,
         description=description,
+        description_i18n=frontmatter.description_i18n,
         type=skill_type,
         dir=dir_path,
         flow=flow,
+        body=body.strip(),
+        model_hint=frontmatter.model or None,
+        temperature=_clamp_temperature(frontmatter.temperature, diagnostics),
+        aliases=frontmatter.aliases,
+        resources=_resolve_resources(frontmatter.resources, dir_path, diagnostics),
+        merge_mode=frontmatter.merge_mode,
+        requires=frontmatter.requires,
+        deprecation=deprecation,
+        protected=frontmatter.protected,
+        depends_on=frontmatter.depends_on,
+        category=frontmatter.category or category,
+        extends=frontmatter.extends,
+        definition_file=file_name,
+        diagnostics=diagnostics,
+        **inheritable,
     )
 
 
-def _parse_flow_from_skill(content: str) -> Flow:
-    for lang, code in _iter_fenced_codeblocks(content):
-        if lang == "mermaid":
-            return _parse_flow_block(parse_mermaid_flowchart, code)
-        if lang == "d2":
-            return _parse_flow_block(parse_d2_flowchart, code)
-    raise ValueError("Flow skills require a mermaid or d2 code block in SKILL.md.")
+def _migrate_frontmatter(
+    data: dict[str, Any], diagnostics: list[SkillDiagnostic]
+) -> dict[str, Any]:
+    """Upgrade frontmatter written for an older `schema_version` to the current schema."""
+    version = data.get("schema_version", FRONTMATTER_SCHEMA_VERSION)
+    if not isinstance(version, int) or isinstance(version, bool):
+        # Left for validation to report and drop.
+        return data
+    if version not in _FRONTMATTER_MIGRATIONS and version != FRONTMATTER_SCHEMA_VERSION:
+        diagnostics.append(
+            SkillDiagnostic(
+                message=f"Unknown `schema_version` {version}; "
+                f"parsing as version {FRONTMATTER_SCHEMA_VERSION}"
+            )
+        )
+        return data
+    data = dict(data)
+    for step in range(version, FRONTMATTER_SCHEMA_VERSION):
+        data = _FRONTMATTER_MIGRATIONS[step](data)
+    return data
 
 
-def _parse_flow_block(parser: Callable[[str], Flow], code: str) -> Flow:
-    try:
-        return parser(code)
-    except FlowError as exc:
-        raise ValueError(f"Invalid flow diagram: {exc}") from exc
+def _migrate_frontmatter_v1(data: dict[str, Any]) -> dict[str, Any]:
+    # v1 wrote tags as a single comma-separated string.
+    tags = data.get("tags")
+    if isinstance(tags, str):
+        data["tags"] = [tag.strip() for tag in tags.split(",") if tag.strip()]
+    return data
+
+
+_FRONTMATTER_MIGRATIONS: dict[int, Callable[[dict[str, Any]], dict[str, Any]]] = {
+    1: _migrate_frontmatter_v1,
+}
+
+
+def _unknown_key_diagnostics(data: dict[str, Any]) -> list[SkillDiagnostic]:
+    known = list(SkillFrontmatter.model_fields)
+    diagnostics: list[SkillDiagnostic] = []
+    for key in data:
+        if key in known:
+            continue
+        message = f"Unknown frontmatter key `{key}`"
+        if suggestion := difflib.get_close_matches(str(key), known, n=1):
+            message += f"; did you mean `{suggestion[0]}`?"
+        diagnostics.append(SkillDiagnostic(message=message))
+    return diagnostics
+
+
+def _load_frontmatter(
+    data: dict[str, Any],
+    *,
+    strict: bool,
+    path: KaosPath,
+    diagnostics: list[SkillDiagnostic],
+) -> SkillFrontmatter:
+    """Validate raw frontmatter, dropping values of the wrong type with a diagnostic."""
+    model = _StrictSkillFrontmatter if strict else SkillFrontmatter
+    data = dict(data)
+    while True:
+        try:
+            return model.model_validate(data)
+        except ValidationError as exc:
+            errors = exc.errors()
+        unknown = [str(error["loc"][0]) for error in errors if error["type"] == "extra_forbidden"]
+        if unknown:
+            keys = ", ".join(f"`{key}`" for key in unknown)
+            raise SkillParseError(f"Unknown frontmatter keys: {keys}", path=path)
+        invalid: dict[str, str] = {}
+        for error in errors:
+            invalid.setdefault(str(error["loc"][0]), error["msg"])
+        for key, msg in invalid.items():
+            diagnostics.append(SkillDiagnostic(message=f"Ignoring invalid `{key}`: {msg}"))
+            data.pop(key, None)
+
+
+def _resolve_resources(
+    resources: list[str], dir_path: KaosPath, diagnostics: list[SkillDiagnostic]
+) -> list[KaosPath]:
+    resolved: list[KaosPath] = []
+    for resource in resources:
+        try:
+            resolved.append(dir_path.join_checked(resource))
+        except ValueError as exc:
+            diagnostics.append(SkillDiagnostic(message=f"Ignoring resource `{resource}`: {exc}"))
+    return resolved
+
+
+def _clamp_temperature(
+    temperature: float | None, diagnostics: list[SkillDiagnostic]
+) -> float | None:
+    if temperature is None:
+        return None
+    low, high = TEMPERATURE_RANGE
+    if not low <= temperature <= high:
+        clamped = min(max(temperature, low), high)
+        diagnostics.append(
+            SkillDiagnostic(
+                message=f"Temperature {temperature} is outside [{low}, {high}]; using {clamped}"
+            )
+        )
+        return clamped
+    return temperature
+
+
+def _attach_node_metadata(
+    value: dict[Any, Any] | None, flow: Flow, diagnostics: list[SkillDiagnostic]
+) -> None:
+    for node_id, raw in (value or {}).items():
+        node_id = str(node_id)
+        node = flow.nodes.get(node_id)
+        if node is None:
+            diagnostics.append(
+                SkillDiagnostic(message=f'`nodes` references unknown node "{node_id}"')
+            )
+            continue
+        if not isinstance(raw, dict):
+            diagnostics.append(
+                SkillDiagnostic(message=f'Metadata for node "{node_id}" must be a mapping')
+            )
+            continue
+        metadata = {str(key): item for key, item in cast(dict[object, Any], raw).items()}
+        flow.nodes[node_id] = replace(node, metadata=metadata)
+
+
+def _parse_node_policies(
+    value: dict[Any, Any] | None, flow: Flow, diagnostics: list[SkillDiagnostic]
+) -> dict[str, FlowNodePolicy]:
+    if value is None:
+        return {}
+
+    policies: dict[str, FlowNodePolicy] = {}
+    for node_id, raw in value.items():
+        node_id = str(node_id)
+        if node_id not in flow.nodes:
+            diagnostics.append(
+                SkillDiagnostic(message=f'`node_policies` references unknown node "{node_id}"')
+            )
+            continue
+        if not isinstance(raw, dict):
+            diagnostics.append(
+                SkillDiagnostic(message=f'Policy for node "{node_id}" must be a mapping')
+            )
+            continue
+        raw = cast(dict[str, object], raw)
+        timeout_secs = raw.get("timeout_secs")
+        retries = raw.get("retries", 0)
+        if timeout_secs is not None and (
+            isinstance(timeout_secs, bool)
+            or not isinstance(timeout_secs, int | float)
+            or timeout_secs <= 0
+        ):
+            diagnostics.append(
+                SkillDiagnostic(message=f'Ignoring invalid timeout_secs for node "{node_id}"')
+            )
+            timeout_secs = None
+        if isinstance(retries, bool) or not isinstance(retries, int) or retries < 0:
+            diagnostics.append(
+                SkillDiagnostic(message=f'Ignoring invalid retries for node "{node_id}"')
+            )
+            retries = 0
+        policies[node_id] = FlowNodePolicy(
+            timeout_secs=float(timeout_secs) if timeout_secs is not None else None,
+            retries=retries,
+        )
+    return policies
+
+
+def parse_flow(text: str, *, lang: FlowLang = "mermaid") -> Flow:
+    """
+    Parse a flow diagram the same way flow skill discovery does.
+
+    Mermaid text may be a `flowchart`/`graph` or a `stateDiagram-v2`.
+
+    Raises:
+        FlowError: If the diagram cannot be parsed or fails structural validation.
+    """
+    return _FLOW_PARSERS[lang](text)
+
+
+def _parse_flow_from_skill(content: str, *, path: KaosPath) -> Flow:
+    for lang, code in _iter_fenced_codeblocks(content):
+        if lang in _FLOW_PARSERS:
+            try:
+                return parse_flow(code, lang=cast(FlowLang, lang))
+            except FlowError as exc:
+                raise SkillFlowError(f"Invalid flow diagram: {exc}", path=path) from exc
+    raise SkillFlowError("Flow skills require a mermaid or d2 code block in SKILL.md.", path=path)
 
 
 def _iter_fenced_codeblocks(content: str) -> Iterator[tuple[str, str]]:
@@ -1,12 +1,17 @@
 from __future__ import annotations
 
 import re
-from dataclasses import dataclass
-from typing import Literal
+from collections.abc import Iterator
+from dataclasses import dataclass, field
+from typing import Any, Literal
 
-from kosong.message import ContentPart
+from kosong.message import ContentPart, Message
+
+_INVOKES_RE = re.compile(r"@skill:([A-Za-z0-9][\w.-]*)")
 
 FlowNodeKind = Literal["begin", "end", "task", "decision"]
+FlowDirection = Literal["TD", "TB", "LR", "RL", "BT"]
+FLOW_DIRECTIONS: tuple[FlowDirection, ...] = ("TD", "TB", "LR", "RL", "BT")
 
 
 class FlowError(ValueError):
@@ -14,18 +19,72 @@ class FlowError(ValueError):
 
 
 class FlowParseError(FlowError):
-    """Raised when prompt flow parsing fails."""
+    """
+    Raised when prompt flow parsing fails.
+
+    `line_no` (1-based, relative to the diagram source) and `line` locate the offending line
+    when known.
+    """
+
+    def __init__(self, reason: str, *, line_no: int | None = None, line: str | None = None):
+        message = reason
+        if line_no is not None:
+            message = f"Line {line_no}: {message}"
+        if line:
+            message = f"{message}: {line}"
+        super().__init__(message)
+        self.reason = reason
+        self.line_no = line_no
+        self.line = line
 
 
 class FlowValidationError(FlowError):
     """Raised when a flowchart fails validation."""
 
 
+class MissingBeginError(FlowValidationError):
+    """Raised when a flowchart has no BEGIN node."""
+
+
+class MultipleBeginError(FlowValidationError):
+    """Raised when a flowchart has more than one BEGIN node."""
+
+    def __init__(self, node_ids: list[str]):
+        ids = ", ".join(f'"{node_id}"' for node_id in node_ids)
+        super().__init__(f"Expected exactly one BEGIN node, found {len(node_ids)}: {ids}")
+        self.node_ids = node_ids
+
+
+class MissingEndError(FlowValidationError):
+    """Raised when a flowchart has no END node."""
+
+
+class EndUnreachableError(FlowValidationError):
+    """Raised when the END node cannot be reached from BEGIN."""
+
+
 @dataclass(frozen=True, slots=True)
 class FlowNode:
     id: str
     label: str | list[ContentPart]
     kind: FlowNodeKind
+    metadata: dict[str, Any] = field(default_factory=dict, compare=False)
+    """Free-form annotations from the skill's `nodes` frontmatter, e.g. `{"tool": "fetch"}`."""
+    link: str | None = field(default=None, compare=False)
+    """URL from a mermaid `click` line, e.g. docs for the step; does not affect the flow."""
+
+    @property
+    def label_text(self) -> str:
+        """The label as plain text, flattening content parts if needed."""
+        if isinstance(self.label, str):
+            return self.label
+        return Message(role="user", content=self.label).extract_text(" ")
+
+    @property
+    def invokes(self) -> str | None:
+        """Name of the skill this node delegates to, from an `@skill:<name>` marker in its label."""
+        match = _INVOKES_RE.search(self.label_text)
+        return match.group(1) if match else None
 
 
 @dataclass(frozen=True, slots=True)
@@ -33,6 +92,25 @@ class FlowEdge:
     src: str
     dst: str
     label: str | None
+    weight: int | None = None
+    """Branch priority parsed from a leading `N:` in the label, e.g. `|1: yes|`."""
+
+
+@dataclass(frozen=True, slots=True)
+class FlowDiagnostic:
+    """A non-fatal problem found while parsing a flow."""
+
+    message: str
+    line_no: int | None = None
+    """1-based line number within the diagram source, if known."""
+
+
+@dataclass(frozen=True, slots=True)
+class FlowNodePolicy:
+    """Execution policy for a single flow node, declared in skill frontmatter."""
+
+    timeout_secs: float | None = None
+    retries: int = 0
 
 
 @dataclass(slots=True)
@@ -41,6 +119,284 @@ class Flow:
     outgoing: dict[str, list[FlowEdge]]
     begin_id: str
     end_id: str
+    node_policies: dict[str, FlowNodePolicy] = field(default_factory=dict)
+    direction: FlowDirection | None = None
+    """Layout direction declared by the diagram; only affects rendering."""
+    diagnostics: list[FlowDiagnostic] = field(default_factory=list)
+
+    def branches_of(self, node_id: str) -> list[tuple[str, str]]:
+        """Return the `(label, target)` pairs of the labeled edges leaving a node."""
+        return [
+            (edge.label, edge.dst)
+            for edge in self.outgoing.get(node_id, [])
+            if edge.label is not None
+        ]
+
+    def traverse(self) -> FlowTraversal:
+        """Walk the nodes reachable from BEGIN in execution order; see `FlowTraversal`."""
+        return FlowTraversal(self)
+
+    def cycles(self) -> list[list[str]]:
+        """
+        Return the node ids of each cycle, one list per strongly connected component.
+
+        A node with a self-loop forms a cycle on its own. Ids follow declaration order.
+        """
+        order = {node_id: idx for idx, node_id in enumerate(self.nodes)}
+        cycles: list[list[str]] = []
+        for component in _strongly_connected_components(self.outgoing):
+            if len(component) == 1:
+                (node_id,) = component
+                if not any(edge.dst == node_id for edge in self.outgoing.get(node_id, [])):
+                    continue
+            cycles.append(sorted(component, key=lambda node_id: order.get(node_id, len(order))))
+        cycles.sort(key=lambda cycle: order.get(cycle[0], len(order)))
+        return cycles
+
+    def is_acyclic(self) -> bool:
+        """Return whether the flow has no loops, including self-loops."""
+        return not self.cycles()
+
+    def unreachable_nodes(self) -> list[str]:
+        """Return the ids of nodes that cannot be reached from BEGIN."""
+        reachable = reachable_from(self.outgoing, self.begin_id)
+        return [node_id for node_id in self.nodes if node_id not in reachable]
+
+    def successors(self, node_id: str) -> list[str]:
+        """Return the ids of the nodes directly reachable from a node."""
+        return list(dict.fromkeys(edge.dst for edge in self.outgoing.get(node_id, [])))
+
+    def to_json(self) -> dict[str, Any]:
+        """
+        Return a JSON-serializable representation of the flow.
+
+        The schema is stable::
+
+            {
+                "begin_id": str,
+                "end_ids": [str],
+                "nodes": [{"id": str, "label": str, "kind": FlowNodeKind}],
+                "edges": [
+                    {"source": str, "target": str, "label": str | None, "weight": int | None}
+                ],
+            }
+
+        Node labels are flattened to plain text; nodes and edges keep declaration order.
+        """
+        return {
+            "begin_id": self.begin_id,
+            "end_ids": [self.end_id],
+            "nodes": [
+                {"id": node.id, "label": node.label_text, "kind": node.kind}
+                for node in self.nodes.values()
+            ],
+            "edges": [
+                {
+                    "source": edge.src,
+                    "target": edge.dst,
+                    "label": edge.label,
+                    "weight": edge.weight,
+                }
+                for edges in self.outgoing.values()
+                for edge in edges
+            ],
+        }
+
+    def to_dot(self) -> str:
+        """Render the flow as a Graphviz `digraph`; decisions are diamonds, terminals ovals."""
+        lines = ["digraph flow {"]
+        if self.direction in ("LR", "RL", "BT"):
+            lines.append(f"    rankdir={self.direction};")
+        for node in self.nodes.values():
+            attrs = f"label={_dot_quote(node.label_text)}, shape={_DOT_SHAPES[node.kind]}"
+            lines.append(f"    {_dot_quote(node.id)} [{attrs}];")
+        for edges in self.outgoing.values():
+            for edge in edges:
+                attrs = f" [label={_dot_quote(edge.label)}]" if edge.label is not None else ""
+                lines.append(f"    {_dot_quote(edge.src)} -> {_dot_quote(edge.dst)}{attrs};")
+        lines.append("}")
+        return "\n".join(lines)
+
+    def to_mermaid(self) -> str:
+        """
+        Render the flow as a normalized mermaid flowchart.
+
+        Nodes are declared first with quoted labels, then one edge per line, then `click`
+        lines for node links; parsing the output yields an equal `Flow`.
+        """
+        header = f"flowchart {self.direction}" if self.direction else "flowchart"
+        lines = [header]
+        for node in self.nodes.values():
+            open_, close = _MERMAID_SHAPES[node.kind]
+            lines.append(f"    {node.id}{open_}{_mermaid_quote(node.label_text)}{close}")
+        for edges in self.outgoing.values():
+            for edge in edges:
+                text = edge.label
+                if edge.weight is not None:
+                    text = f"{edge.weight}: {text or ''}".rstrip()
+                link = "-->" if text is None else f"-->|{_mermaid_edge_text(text)}|"
+                lines.append(f"    {edge.src} {link} {edge.dst}")
+        lines.extend(
+            f'    click {node.id} href "{node.link}"'
+            for node in self.nodes.values()
+            if node.link is not None
+        )
+        return "\n".join(lines)
+
+    def predecessors(self, node_id: str) -> list[str]:
+        """Return the ids of the nodes with an edge into a node."""
+        return list(
+            dict.fromkeys(
+                edge.src
+                for edges in self.outgoing.values()
+                for edge in edges
+                if edge.dst == node_id
+            )
+        )
+
+
+@dataclass(frozen=True, slots=True)
+class FlowStep:
+    """A node visited by `FlowTraversal`, with the edges the caller may need to act on."""
+
+    node: FlowNode
+    branches: list[tuple[str, str]]
+    """Labeled `(label, target)` edges, for picking a branch at decision nodes."""
+    back_edges: list[FlowEdge]
+    """Edges from this node that loop back to an earlier step; never followed."""
+
+
+class FlowTraversal(Iterator[FlowStep]):
+    """
+    Iterate the nodes reachable from BEGIN in topological order.
+
+    Loops are broken by treating edges back into the current DFS path as back-edges: they are
+    exposed on the step of their source node and excluded from the ordering, so traversal
+    always terminates.
+    """
+
+    def __init__(self, flow: Flow) -> None:
+        self.back_edges: list[FlowEdge] = _find_back_edges(flow.outgoing, flow.begin_id)
+        self._steps = iter(self._order(flow))
+
+    def __next__(self) -> FlowStep:
+        return next(self._steps)
+
+    def _order(self, flow: Flow) -> list[FlowStep]:
+        back = set(self.back_edges)
+        reachable = reachable_from(flow.outgoing, flow.begin_id)
+        indegree = dict.fromkeys(reachable, 0)
+        for node_id in reachable:
+            for edge in flow.outgoing.get(node_id, []):
+                if edge not in back:
+                    indegree[edge.dst] += 1
+
+        steps: list[FlowStep] = []
+        queue = [flow.begin_id]
+        while queue:
+            node_id = queue.pop(0)
+            edges = flow.outgoing.get(node_id, [])
+            steps.append(
+                FlowStep(
+                    node=flow.nodes[node_id],
+                    branches=flow.branches_of(node_id),
+                    back_edges=[edge for edge in edges if edge in back],
+                )
+            )
+            for edge in edges:
+                if edge in back:
+                    continue
+                indegree[edge.dst] -= 1
+                if indegree[edge.dst] == 0:
+                    queue.append(edge.dst)
+        return steps
+
+
+def _strongly_connected_components(outgoing: dict[str, list[FlowEdge]]) -> list[set[str]]:
+    """Tarjan's algorithm over `outgoing`."""
+    index: dict[str, int] = {}
+    lowlink: dict[str, int] = {}
+    stack: list[str] = []
+    on_stack: set[str] = set()
+    components: list[set[str]] = []
+
+    def visit(node_id: str) -> None:
+        index[node_id] = lowlink[node_id] = len(index)
+        stack.append(node_id)
+        on_stack.add(node_id)
+        for edge in outgoing.get(node_id, []):
+            if edge.dst not in index:
+                visit(edge.dst)
+                lowlink[node_id] = min(lowlink[node_id], lowlink[edge.dst])
+            elif edge.dst in on_stack:
+                lowlink[node_id] = min(lowlink[node_id], index[edge.dst])
+        if lowlink[node_id] == index[node_id]:
+            component: set[str] = set()
+            while True:
+                member = stack.pop()
+                on_stack.discard(member)
+                component.add(member)
+                if member == node_id:
+                    break
+            components.append(component)
+
+    for node_id in outgoing:
+        if node_id not in index:
+            visit(node_id)
+    return components
+
+
+def _find_back_edges(outgoing: dict[str, list[FlowEdge]], start: str) -> list[FlowEdge]:
+    """Return the edges that close a cycle during a DFS from `start`."""
+    back_edges: list[FlowEdge] = []
+    on_path: set[str] = {start}
+    done: set[str] = set()
+    stack: list[tuple[str, Iterator[FlowEdge]]] = [(start, iter(outgoing.get(start, [])))]
+    while stack:
+        node_id, edges = stack[-1]
+        for edge in edges:
+            if edge.dst in on_path:
+                back_edges.append(edge)
+            elif edge.dst not in done:
+                on_path.add(edge.dst)
+                stack.append((edge.dst, iter(outgoing.get(edge.dst, []))))
+                break
+        else:
+            on_path.discard(node_id)
+            done.add(node_id)
+            stack.pop()
+    return back_edges
+
+
+_DOT_SHAPES: dict[FlowNodeKind, str] = {
+    "begin": "oval",
+    "end": "oval",
+    "task": "box",
+    "decision": "diamond",
+}
+
+
+_MERMAID_SHAPES: dict[FlowNodeKind, tuple[str, str]] = {
+    "begin": ("([", "])"),
+    "end": ("([", "])"),
+    "task": ("[", "]"),
+    "decision": ("{", "}"),
+}
+
+
+def _mermaid_quote(text: str) -> str:
+    escaped = text.replace("\\", "\\\\").replace('"', '\\"')
+    return f'"{escaped}"'
+
+
+def _mermaid_edge_text(text: str) -> str:
+    # Pipe labels are taken verbatim, so only quote when the text would end the label early.
+    return f'"{text}"' if "|" in text else text
+
+
+def _dot_quote(text: str) -> str:
+    escaped = text.replace("\\", "\\\\").replace('"', '\\"').replace("\n", "\\n")
+    return f'"{escaped}"'
 
 
 _CHOICE_RE = re.compile(r"<choice>([^<]*)</choice>")
@@ -53,47 +409,84 @@ def parse_choice(text: str) -> str | None:
     return matches[-1].strip()
 
 
+def reachable_from(outgoing: dict[str, list[FlowEdge]], start: str) -> set[str]:
+    """Return the ids of all nodes reachable from `start`, including itself."""
+    reachable: set[str] = set()
+    queue: list[str] = [start]
+    while queue:
+        node_id = queue.pop()
+        if node_id in reachable:
+            continue
+        reachable.add(node_id)
+        for edge in outgoing.get(node_id, []):
+            if edge.dst not in reachable:
+                queue.append(edge.dst)
+    return reachable
+
+
+def lint_flow(flow: Flow) -> list[FlowDiagnostic]:
+    """
+    Return the non-fatal problems of a parsed flow.
+
+    Structural errors are raised by `validate_flow` while parsing; this reports what a valid
+    flow can still contain, such as redefined or unreachable nodes, or decision branches
+    that cannot be told apart because only some of their edges have a label.
+    """
+    diagnostics = list(flow.diagnostics)
+    diagnostics.extend(
+        FlowDiagnostic(message=f'Flow node "{node_id}" is not reachable from BEGIN')
+        for node_id in flow.unreachable_nodes()
+    )
+    for node_id, edges in flow.outgoing.items():
+        unlabeled = sum(1 for edge in edges if edge.label is None or not edge.label.strip())
+        # Fan-out with no labels at all is a fork; labeling only some edges is a mistake.
+        if 0 < unlabeled < len(edges):
+            diagnostics.append(
+                FlowDiagnostic(
+                    message=(
+                        f'Flow node "{node_id}" has {unlabeled} unlabeled of {len(edges)} '
+                        "outgoing edges"
+                    )
+                )
+            )
+    return diagnostics
+
+
 def validate_flow(
     nodes: dict[str, FlowNode],
     outgoing: dict[str, list[FlowEdge]],
 ) -> tuple[str, str]:
+    """Check the structural rules every flow must satisfy; returns the BEGIN and END ids."""
     begin_ids = [node.id for node in nodes.values() if node.kind == "begin"]
     end_ids = [node.id for node in nodes.values() if node.kind == "end"]
 
+    if not begin_ids:
+        raise MissingBeginError("Expected exactly one BEGIN node, found 0")
     if len(begin_ids) != 1:
-        raise FlowValidationError(f"Expected exactly one BEGIN node, found {len(begin_ids)}")
+        raise MultipleBeginError(begin_ids)
+    if not end_ids:
+        raise MissingEndError("Expected exactly one END node, found 0")
     if len(end_ids) != 1:
         raise FlowValidationError(f"Expected exactly one END node, found {len(end_ids)}")
 
     begin_id = begin_ids[0]
     end_id = end_ids[0]
 
-    reachable: set[str] = set()
-    queue: list[str] = [begin_id]
-    while queue:
-        node_id = queue.pop()
-        if node_id in reachable:
-            continue
-        reachable.add(node_id)
-        for edge in outgoing.get(node_id, []):
-            if edge.dst not in reachable:
-                queue.append(edge.dst)
+    reachable = reachable_from(outgoing, begin_id)
 
     for node in nodes.values():
         if node.id not in reachable:
             continue
-        edges = outgoing.get(node.id, [])
-        if len(edges) <= 1:
-            continue
-        labels: list[str] = []
-        for edge in edges:
-            if edge.label is None or not edge.label.strip():
-                raise FlowValidationError(f'Node "{node.id}" has an unlabeled edge')
-            labels.append(edge.label)
+        # Unlabeled fan-out is a fork, so only the labels that are present must differ.
+        labels = [
+            edge.label
+            for edge in outgoing.get(node.id, [])
+            if edge.label is not None and edge.label.strip()
+        ]
         if len(set(labels)) != len(labels):
             raise FlowValidationError(f'Node "{node.id}" has duplicate edge labels')
 
     if end_id not in reachable:
-        raise FlowValidationError("END node is not reachable from BEGIN")
+        raise EndUnreachableError("END node is not reachable from BEGIN")
 
     return begin_id, end_id
@@ -108,7 +108,7 @@ def _normalize_markdown_blocks(text: str) -> str:
             i += 1
             line_no += 1
         if i >= len(lines):
-            raise FlowParseError(_line_error(start_line, "Unclosed markdown block"))
+            raise FlowParseError("Unclosed markdown block", line_no=start_line)
 
         # Convert the block into a multiline quoted string label.
         dedented = _dedent_block(block_lines)
@@ -228,7 +228,7 @@ def _iter_top_level_statements(text: str) -> Iterable[tuple[int, str]]:
                 i += 1
                 continue
             if ch == "}" and brace_depth == 0:
-                raise FlowParseError(_line_error(line_no, "Unmatched '}'"))
+                raise FlowParseError("Unmatched '}'", line_no=line_no)
 
         if ch == "'" and not in_double and not escape:
             in_single = not in_single
@@ -246,9 +246,9 @@ def _iter_top_level_statements(text: str) -> Iterable[tuple[int, str]]:
         i += 1
 
     if brace_depth != 0:
-        raise FlowParseError(_line_error(line_no, "Unclosed '{' block"))
+        raise FlowParseError("Unclosed '{' block", line_no=line_no)
     if in_single or in_double:
-        raise FlowParseError(_line_error(line_no, "Unclosed string"))
+        raise FlowParseError("Unclosed string", line_no=line_no)
 
     statement = "".join(buf).strip()
     if statement:
@@ -268,7 +268,7 @@ def _parse_edge_statement(
 ) -> None:
     parts = _split_on_token(statement, "->")
     if len(parts) < 2:
-        raise FlowParseError(_line_error(line_no, "Expected edge arrow"))
+        raise FlowParseError("Expected edge arrow", line_no=line_no)
 
     last_part = parts[-1]
     target_text, edge_label = _split_unquoted_once(last_part, ":")
@@ -282,7 +282,7 @@ def _parse_edge_statement(
     if any(_is_property_path(node_id) for node_id in node_ids):
         return
     if len(node_ids) < 2:
-        raise FlowParseError(_line_error(line_no, "Edge must have at least two nodes"))
+        raise FlowParseError("Edge must have at least two nodes", line_no=line_no)
 
     label = _parse_label(edge_label, line_no) if edge_label is not None else None
     for idx in range(len(node_ids) - 1):
@@ -318,10 +318,10 @@ def _parse_node_id(text: str, line_no: int, *, allow_inline_label: bool) -> str:
     if allow_inline_label and ":" in cleaned:
         cleaned = _split_unquoted_once(cleaned, ":")[0].strip()
     if not cleaned:
-        raise FlowParseError(_line_error(line_no, "Expected node id"))
+        raise FlowParseError("Expected node id", line_no=line_no)
     match = _NODE_ID_RE.fullmatch(cleaned)
     if not match:
-        raise FlowParseError(_line_error(line_no, f'Invalid node id "{cleaned}"'))
+        raise FlowParseError(f'Invalid node id "{cleaned}"', line_no=line_no)
     return match.group(0)
 
 
@@ -338,7 +338,7 @@ def _is_property_path(node_id: str) -> bool:
 def _parse_label(text: str, line_no: int) -> str:
     label = text.strip()
     if not label:
-        raise FlowParseError(_line_error(line_no, "Label cannot be empty"))
+        raise FlowParseError("Label cannot be empty", line_no=line_no)
     if label[0] in {"'", '"'}:
         return _parse_quoted_label(label, line_no)
     return label
@@ -363,11 +363,11 @@ def _parse_quoted_label(text: str, line_no: int) -> str:
         if ch == quote:
             trailing = text[i + 1 :].strip()
             if trailing:
-                raise FlowParseError(_line_error(line_no, "Unexpected trailing content"))
+                raise FlowParseError("Unexpected trailing content", line_no=line_no)
             return "".join(buf)
         buf.append(ch)
         i += 1
-    raise FlowParseError(_line_error(line_no, "Unclosed quoted label"))
+    raise FlowParseError("Unclosed quoted label", line_no=line_no)
 
 
 def _split_on_token(text: str, token: str) -> list[str]:
@@ -435,7 +435,7 @@ def _add_node(
     label = label if label is not None else node_id
     label_norm = label.strip().lower()
     if not label:
-        raise FlowParseError(_line_error(line_no, "Node label cannot be empty"))
+        raise FlowParseError("Node label cannot be empty", line_no=line_no)
 
     kind: FlowNodeKind = "task"
     if label_norm == "begin":
@@ -459,7 +459,7 @@ def _add_node(
         nodes[node_id] = _NodeDef(node=node, explicit=True)
         return node
 
-    raise FlowParseError(_line_error(line_no, f'Conflicting definition for node "{node_id}"'))
+    raise FlowParseError(f'Conflicting definition for node "{node_id}"', line_no=line_no)
 
 
 def _infer_decision_nodes(
@@ -469,7 +469,9 @@ def _infer_decision_nodes(
     updated: dict[str, FlowNode] = {}
     for node_id, node in nodes.items():
         kind = node.kind
-        if kind == "task" and len(outgoing.get(node_id, [])) > 1:
+        edges = outgoing.get(node_id, [])
+        # A task fanning out along unlabeled edges only is a fork, not a decision.
+        if kind == "task" and len(edges) > 1 and any(edge.label for edge in edges):
             kind = "decision"
         if kind != node.kind:
             updated[node_id] = FlowNode(id=node.id, label=node.label, kind=kind)
@@ -477,6 +479,3 @@ def _infer_decision_nodes(
             updated[node_id] = node
     return updated
 
-
-def _line_error(line_no: int, message: str) -> str:
-    return f"Line {line_no}: {message}"
@@ -0,0 +1,694 @@
+from __future__ import annotations
+
+import asyncio
+import copy
+import time
+from collections.abc import Awaitable, Callable
+from dataclasses import dataclass, field
+from typing import Any, Literal
+
+from loguru import logger
+from pydantic import TypeAdapter, ValidationError
+
+from . import Flow, FlowEdge, FlowError, FlowNode, reachable_from
+
+StepKind = Literal["advanced", "done", "needs_decision", "cancelled", "step_limit_exceeded"]
+TraceOutcome = Literal["ok", "err", "timeout"]
+
+_VISITED_NODE_STYLE = "fill:#fde68a,stroke:#d97706,stroke-width:2px"
+_TAKEN_EDGE_STYLE = "stroke:#d97706,stroke-width:2px"
+_DRY_RUN_LOOP_LIMIT = 100
+"""Entries per node a dry run allows when no loop limit is set, so resolver loops end."""
+
+
+class FlowExecutionError(FlowError):
+    """Raised when a flow cannot advance from its current node."""
+
+
+class FlowNodeTimeoutError(FlowExecutionError):
+    """Raised when a node handler runs longer than the executor's node timeout."""
+
+    def __init__(self, node_id: str, timeout: float):
+        super().__init__(f'Node "{node_id}" timed out after {timeout}s')
+        self.node_id = node_id
+        self.timeout = timeout
+
+
+class FlowLoopLimitError(FlowExecutionError):
+    """Raised when a node would be entered more often than the executor's loop limit."""
+
+    def __init__(self, node_id: str, limit: int):
+        super().__init__(f'Node "{node_id}" exceeded the loop limit of {limit} entries')
+        self.node_id = node_id
+        self.limit = limit
+
+
+class FlowBindingError(FlowExecutionError):
+    """Raised when a binding does not have the type it is read as."""
+
+    def __init__(self, key: str, error: ValidationError):
+        super().__init__(f'Binding "{key}" is not a valid {error.title}')
+        self.key = key
+        self.error = error
+
+
+@dataclass(frozen=True, slots=True)
+class StepOutcome:
+    kind: StepKind
+    node_id: str
+    """The node the executor is at after the step."""
+
+
+@dataclass(frozen=True, slots=True)
+class TraceEntry:
+    """One node entry recorded in `FlowExecutor.trace`."""
+
+    node_id: str
+    entered_at: float
+    """Wall-clock time the node was entered, as returned by `time.time()`."""
+    duration: float
+    """Seconds spent in the node's handler; zero for nodes without one."""
+    outcome: TraceOutcome
+    """How the handler finished; `err` is recorded whatever the node's error policy."""
+
+
+@dataclass(slots=True)
+class FlowContext:
+    """State shared with node handlers during an execution."""
+
+    flow: Flow
+    node: FlowNode
+    """The node being entered."""
+    bindings: dict[str, Any] = field(default_factory=dict)
+    """Values written and read by node handlers, kept for the whole execution."""
+    outcome: StepOutcome | None = None
+    """How `FlowExecutor.run_with` stopped: `done`, `cancelled` or `step_limit_exceeded`."""
+
+    def get_as[T](self, key: str, type_: type[T]) -> T:
+        """
+        Read the binding `key` validated as `type_`, e.g. a model stored with `set_typed`.
+
+        Raises:
+            KeyError: If there is no such binding.
+            FlowBindingError: If the value does not validate as `type_`.
+        """
+        try:
+            return TypeAdapter(type_).validate_python(self.bindings[key])
+        except ValidationError as exc:
+            raise FlowBindingError(key, exc) from exc
+
+    def set_typed(self, key: str, value: Any) -> None:
+        """Store `value` under `key` as plain JSON data, to be read back with `get_as`."""
+        self.bindings[key] = TypeAdapter(type(value)).dump_python(value, mode="json")
+
+
+@dataclass(frozen=True, slots=True)
+class ErrorBranch:
+    """Error policy that leaves a failing node along its outgoing edge labeled `label`."""
+
+    label: str
+
+
+@dataclass(frozen=True, slots=True)
+class RetryPolicy:
+    """How often to run a failing node handler, and how long to wait in between."""
+
+    attempts: int
+    """Total number of runs, including the first."""
+    backoff: float = 0.0
+    """Seconds to wait before the first retry, doubled before each later one."""
+
+
+type ErrorPolicy = Literal["abort", "continue"] | ErrorBranch
+"""What to do when a node handler raises: re-raise, move on as usual, or take a branch."""
+type NodeHandler = Callable[[FlowContext], Awaitable[None]]
+type BranchResolver = Callable[[FlowNode, list[FlowEdge]], str]
+"""Picks the edge to follow from a node, by edge label or target id."""
+type TransitionHook = Callable[[str, str], None]
+"""Called with the `(from, to)` node ids of each transition; `from` is `""` on entering."""
+type BranchMerge = Callable[[list[dict[str, Any]]], dict[str, Any]]
+"""Combines the bindings of parallel branches, in edge order, into the bindings to go on with."""
+type SubflowResolver = Callable[[str], Flow | None]
+"""Returns the flow of the skill a node's `subflow` metadata names, or `None` if unknown."""
+
+
+class FlowExecutor:
+    """
+    Step through a flow one edge at a time, starting at its BEGIN node.
+
+    Nodes with several outgoing edges are resolved by the branch resolver; without one the
+    executor stops there with a `needs_decision` outcome. Handlers registered with `on_node`
+    run when their node is entered; nodes without one are passed through. `name` is the
+    skill the flow belongs to, used to detect sub-flows that invoke it again.
+    """
+
+    def __init__(self, flow: Flow, *, name: str | None = None) -> None:
+        self._flow = flow
+        self._current_id = flow.begin_id
+        self._started = False
+        self._handlers: dict[str, NodeHandler] = {}
+        self._resolver: BranchResolver | None = None
+        self._node_timeout: float | None = None
+        self._max_steps: int | None = None
+        self._transitions = 0
+        self._cancel_event: asyncio.Event | None = None
+        self._loop_limit: int | None = None
+        self._entries: dict[str, int] = {}
+        self._visited: list[str] = []
+        self._trace: list[TraceEntry] = []
+        self._taken: set[tuple[str, str]] = set()
+        self._transition_hooks: list[TransitionHook] = []
+        self._merge: BranchMerge | None = None
+        self._error_policies: dict[str, ErrorPolicy] = {}
+        self._error_branches: dict[str, str] = {}
+        self._retries: dict[str, RetryPolicy] = {}
+        self._subflow_resolver: SubflowResolver | None = None
+        self._subflow_handlers: dict[str, dict[str, NodeHandler]] = {}
+        self._subflow_stack: tuple[str, ...] = (name,) if name is not None else ()
+        self._context = FlowContext(flow=flow, node=flow.nodes[flow.begin_id])
+
+    @property
+    def current_id(self) -> str:
+        """The node the executor is at; BEGIN until the first step."""
+        return self._current_id
+
+    @property
+    def context(self) -> FlowContext:
+        """The context handlers get, for inspecting bindings between manual `step` calls."""
+        return self._context
+
+    @property
+    def visited(self) -> list[str]:
+        """Ids of the nodes entered so far, in order, e.g. for a breadcrumb trail."""
+        return list(self._visited)
+
+    @property
+    def trace(self) -> list[TraceEntry]:
+        """One entry per node entered so far, in order, with handler timing and outcome."""
+        return list(self._trace)
+
+    def on_node(self, node_id: str, handler: NodeHandler) -> None:
+        """
+        Run `handler` whenever the node is entered, replacing any previous handler.
+
+        Raises:
+            KeyError: If the flow has no such node.
+        """
+        if node_id not in self._flow.nodes:
+            raise KeyError(node_id)
+        self._handlers[node_id] = handler
+
+    def on_transition(self, hook: TransitionHook) -> None:
+        """
+        Call `hook` with `(from, to)` for every edge taken, in addition to earlier hooks.
+
+        Entering the first node of a run, BEGIN or a `resume_at` node, reports `from` as `""`.
+        Hooks run before the handler of the node being entered.
+        """
+        self._transition_hooks.append(hook)
+
+    def set_branch_resolver(self, resolver: BranchResolver | None) -> None:
+        """Use `resolver` to choose among several outgoing edges; `None` removes it."""
+        self._resolver = resolver
+
+    def set_parallel(self, merge: BranchMerge | None) -> None:
+        """
+        Run the branches of a fork concurrently and combine their bindings with `merge`.
+
+        A fork is a node with several outgoing edges that are all unlabeled, or whose metadata
+        sets `fork: true`; other nodes with several edges are still decided by the branch
+        resolver. Each branch runs with a deep copy of the bindings, from its first node up to
+        the join: the earliest node in execution order that every branch reaches. Branch
+        nodes are otherwise followed like a normal run, including the cancel and step limit
+        checks of `run_with`; a branch stopped by those leaves the executor at the fork. A fork
+        within a branch runs its own branches the same way. Once all branches are done, the
+        join is entered once, as a transition from the fork node, with the merged bindings. If
+        a branch raises, the others are cancelled and the error propagates. `None` turns
+        parallel mode off.
+        """
+        self._merge = merge
+
+    def set_error_policy(self, node_id: str, policy: ErrorPolicy) -> None:
+        """
+        Decide what happens when the handler of the node raises; the default is `"abort"`.
+
+        `"abort"` lets the error propagate out of `step` or `run`. `"continue"` logs it and
+        leaves the node as if the handler had succeeded. `ErrorBranch(label)` logs it and
+        leaves the node along the edge with that label, bypassing the branch resolver. Node
+        timeouts count as handler errors.
+
+        Raises:
+            KeyError: If the flow has no such node.
+            FlowExecutionError: If `policy` is an `ErrorBranch` whose label is not on any
+                outgoing edge of the node.
+        """
+        if node_id not in self._flow.nodes:
+            raise KeyError(node_id)
+        if isinstance(policy, ErrorBranch) and not any(
+            edge.label == policy.label for edge in self._flow.outgoing.get(node_id, [])
+        ):
+            raise FlowExecutionError(f'Node "{node_id}" has no "{policy.label}" branch')
+        self._error_policies[node_id] = policy
+
+    def set_retry(self, node_id: str, policy: RetryPolicy | None) -> None:
+        """
+        Retry the handler of the node when it raises, before its error policy applies.
+
+        Timeouts count as failures. `None` removes the policy, so the handler runs once.
+
+        Raises:
+            KeyError: If the flow has no such node.
+            ValueError: If `policy.attempts` is less than 1.
+        """
+        if node_id not in self._flow.nodes:
+            raise KeyError(node_id)
+        if policy is None:
+            self._retries.pop(node_id, None)
+            return
+        if policy.attempts < 1:
+            raise ValueError("Retry policy needs at least one attempt")
+        self._retries[node_id] = policy
+
+    def set_subflow_resolver(self, resolver: SubflowResolver | None) -> None:
+        """
+        Run the flow of another skill when entering a node with `subflow: <skill>` metadata.
+
+        The sub-flow runs after the node's own handler, in a child executor that shares this
+        one's branch resolver, node timeout, step and loop limits, parallel mode, transition
+        hooks and the cancel event of the current run. Error and retry policies are set per
+        node of this flow, so the child does not inherit them: a failing sub-flow node aborts
+        the sub-flow, which counts as a failure of the node that invoked it. The sub-flow
+        starts with a copy of the bindings, which are merged back once it reaches END; if the
+        run is cancelled meanwhile, they are dropped. `None` removes the resolver.
+        """
+        self._subflow_resolver = resolver
+
+    def on_subflow_node(self, skill_name: str, node_id: str, handler: NodeHandler) -> None:
+        """Run `handler` whenever node `node_id` of the sub-flow `skill_name` is entered."""
+        self._subflow_handlers.setdefault(skill_name, {})[node_id] = handler
+
+    def set_node_timeout(self, timeout: float | None) -> None:
+        """Limit each node handler to `timeout` seconds; `None` removes the limit."""
+        self._node_timeout = timeout
+
+    def set_max_steps(self, max_steps: int | None) -> None:
+        """Stop a run after `max_steps` transitions; `None`, the default, means no limit."""
+        self._max_steps = max_steps
+
+    def set_loop_limit(self, per_node: int | None) -> None:
+        """
+        Allow each node to be entered at most `per_node` times; `None` removes the limit.
+
+        Entries are counted afresh by every `run`, `run_with` and `resume_at` call.
+        """
+        self._loop_limit = per_node
+
+    async def step(self) -> StepOutcome:
+        """
+        Advance along an outgoing edge of the current node.
+
+        The first step enters BEGIN before leaving it. Returns a `done` outcome once an END
+        node is reached; stepping again stays there. A node with several outgoing edges and no
+        branch resolver yields `needs_decision` without moving, unless it is a fork in parallel
+        mode, where one step runs all branches and enters the join; see `set_parallel`.
+
+        Raises:
+            FlowExecutionError: If the current node has no outgoing edges, or the resolver
+                picks a branch that does not exist.
+            FlowNodeTimeoutError: If the handler of the node entered exceeds the node timeout.
+                The executor stays at that node.
+            Exception: Whatever the handler of the node entered raised, if its error policy is
+                `"abort"`. The executor stays at that node.
+            FlowLoopLimitError: If the node to enter has already reached the loop limit.
+        """
+        if not self._started:
+            self._started = True
+            await self._enter("", self._current_id, self._context)
+        if self._flow.nodes[self._current_id].kind == "end":
+            return StepOutcome(kind="done", node_id=self._current_id)
+
+        edges = self._flow.outgoing.get(self._current_id, [])
+        if self._is_fork(self._current_id, edges):
+            fork_id = self._current_id
+            join_id = await self._run_fork(fork_id, edges, self._context)
+            if join_id is None:
+                stopped = self._stop_reason()
+                assert stopped is not None
+                return StepOutcome(kind=stopped, node_id=fork_id)
+            self._current_id = join_id
+            await self._enter(fork_id, join_id, self._context)
+        else:
+            edge = self._choose_edge(self._current_id)
+            if edge is None:
+                return StepOutcome(kind="needs_decision", node_id=self._current_id)
+            self._current_id = edge.dst
+            await self._enter(edge.src, edge.dst, self._context)
+        self._transitions += 1
+        kind: StepKind = "done" if self._flow.nodes[self._current_id].kind == "end" else "advanced"
+        return StepOutcome(kind=kind, node_id=self._current_id)
+
+    async def run(self, cancel_event: asyncio.Event | None = None) -> FlowContext:
+        """Step until an END node is reached, awaiting each handler in turn; see `run_with`."""
+        return await self.run_with(self._context, cancel_event=cancel_event)
+
+    async def run_with(
+        self, context: FlowContext, *, cancel_event: asyncio.Event | None = None
+    ) -> FlowContext:
+        """
+        Step until an END node is reached, passing `context` to every handler.
+
+        Returns the same context once the flow is done, with whatever bindings the handlers
+        left in it. `cancel_event` is checked before each node is entered; once it is set the
+        run stops early and `context.outcome` is `cancelled` at the last node reached. Likewise
+        the run stops with `step_limit_exceeded` once `set_max_steps` transitions were taken
+        without reaching END.
+
+        Raises:
+            FlowExecutionError: If the flow cannot advance, including a decision node with no
+                branch resolver.
+        """
+        self._context = context
+        self._cancel_event = cancel_event
+        self._transitions = 0
+        self._entries.clear()
+        try:
+            while True:
+                if (stopped := self._stop_reason()) is not None:
+                    outcome = StepOutcome(kind=stopped, node_id=self._current_id)
+                    break
+                outcome = await self.step()
+                if outcome.kind in ("done", "cancelled", "step_limit_exceeded"):
+                    break
+                if outcome.kind == "needs_decision":
+                    raise FlowExecutionError(
+                        f'Node "{outcome.node_id}" needs a branch decision but no resolver is set'
+                    )
+        finally:
+            self._cancel_event = None
+        context.outcome = outcome
+        return context
+
+    async def resume_at(
+        self,
+        node_id: str,
+        context: FlowContext,
+        *,
+        cancel_event: asyncio.Event | None = None,
+    ) -> FlowContext:
+        """
+        Restart an interrupted run at `node_id` with a restored `context`; see `run_with`.
+
+        The node is entered again, so its handler runs before the executor moves on.
+
+        Raises:
+            FlowExecutionError: If the flow has no such node, or cannot advance from there.
+        """
+        if node_id not in self._flow.nodes:
+            raise FlowExecutionError(f'Cannot resume at unknown node "{node_id}"')
+        self._current_id = node_id
+        self._started = False
+        return await self.run_with(context, cancel_event=cancel_event)
+
+    def dry_run(self) -> list[str]:
+        """
+        Return the node ids a run would visit from BEGIN to END, without running handlers.
+
+        Decisions are made by the branch resolver, which is called just as in a real run. The
+        executor's state is left untouched, including error branches still to be taken. In
+        parallel mode the branches of a fork are listed one after the other, in edge order,
+        followed by the join. Like a run, a dry run honors `set_max_steps` and
+        `set_loop_limit`; without a loop limit each node may be entered at most 100 times.
+
+        Raises:
+            FlowExecutionError: If the flow cannot advance, including a decision node with no
+                branch resolver, or takes more than `max_steps` transitions.
+            FlowLoopLimitError: If a node would be entered more often than the loop limit.
+        """
+        begin_id = self._flow.begin_id
+        path = [begin_id]
+        self._dry_walk(begin_id, None, path, {begin_id: 1})
+        return path
+
+    def to_highlighted_mermaid(self) -> str:
+        """
+        Render the flow like `Flow.to_mermaid`, with the path taken so far emphasized.
+
+        Visited nodes get a `style` line and the edges followed a `linkStyle` line; everything
+        else keeps the default look.
+        """
+        lines = [self._flow.to_mermaid()]
+        visited = set(self._visited)
+        lines.extend(
+            f"    style {node_id} {_VISITED_NODE_STYLE}"
+            for node_id in self._flow.nodes
+            if node_id in visited
+        )
+        edges = [edge for edges in self._flow.outgoing.values() for edge in edges]
+        taken = [str(i) for i, edge in enumerate(edges) if (edge.src, edge.dst) in self._taken]
+        if taken:
+            lines.append(f"    linkStyle {','.join(taken)} {_TAKEN_EDGE_STYLE}")
+        return "\n".join(lines)
+
+    def _dry_walk(
+        self, node_id: str, stop_id: str | None, path: list[str], entries: dict[str, int]
+    ) -> None:
+        """Extend `path` from `node_id` until END, or until `stop_id` would be entered."""
+        loop_limit = self._loop_limit if self._loop_limit is not None else _DRY_RUN_LOOP_LIMIT
+        while self._flow.nodes[node_id].kind != "end":
+            edges = self._flow.outgoing.get(node_id, [])
+            if self._is_fork(node_id, edges):
+                next_id = self._find_fork_join(node_id, edges)
+                for edge in edges:
+                    if edge.dst != next_id:
+                        self._dry_enter(edge.dst, path, entries, loop_limit)
+                        self._dry_walk(edge.dst, next_id, path, entries)
+            else:
+                edge = self._pick_edge(node_id)
+                if edge is None:
+                    raise FlowExecutionError(
+                        f'Node "{node_id}" needs a branch decision but no resolver is set'
+                    )
+                next_id = edge.dst
+            if next_id == stop_id:
+                return
+            self._dry_enter(next_id, path, entries, loop_limit)
+            node_id = next_id
+
+    def _dry_enter(
+        self, node_id: str, path: list[str], entries: dict[str, int], loop_limit: int
+    ) -> None:
+        if self._max_steps is not None and len(path) > self._max_steps:
+            raise FlowExecutionError(f"Dry run did not reach END within {self._max_steps} steps")
+        entries[node_id] = entries.get(node_id, 0) + 1
+        if entries[node_id] > loop_limit:
+            raise FlowLoopLimitError(node_id, loop_limit)
+        path.append(node_id)
+
+    def _is_fork(self, node_id: str, edges: list[FlowEdge]) -> bool:
+        if self._merge is None or len(edges) <= 1 or node_id in self._error_branches:
+            return False
+        if self._flow.nodes[node_id].metadata.get("fork") is True:
+            return True
+        return all(edge.label is None or not edge.label.strip() for edge in edges)
+
+    def _stop_reason(self) -> StepKind | None:
+        """Why `run_with` must stop before the next transition, if it must."""
+        if self._cancel_event is not None and self._cancel_event.is_set():
+            return "cancelled"
+        if self._max_steps is not None and self._transitions >= self._max_steps:
+            return "step_limit_exceeded"
+        return None
+
+    async def _run_fork(
+        self, fork_id: str, edges: list[FlowEdge], context: FlowContext
+    ) -> str | None:
+        """
+        Run the branches leaving `fork_id` concurrently and return the join node id.
+
+        The branches start from the bindings of `context`, which get the merged result.
+        Returns `None`, leaving the bindings unmerged, if a branch was stopped early.
+        """
+        assert self._merge is not None
+        join_id = self._find_fork_join(fork_id, edges)
+        contexts = [
+            FlowContext(
+                flow=self._flow,
+                node=self._flow.nodes[edge.dst],
+                bindings=copy.deepcopy(context.bindings),
+            )
+            for edge in edges
+        ]
+        tasks = [
+            asyncio.create_task(self._run_branch(edge, join_id, branch_context))
+            for edge, branch_context in zip(edges, contexts, strict=True)
+        ]
+        try:
+            finished = await asyncio.gather(*tasks)
+        except BaseException:
+            for task in tasks:
+                task.cancel()
+            await asyncio.gather(*tasks, return_exceptions=True)
+            raise
+        if not all(finished):
+            return None
+        context.bindings = self._merge([branch.bindings for branch in contexts])
+        return join_id
+
+    def _find_fork_join(self, fork_id: str, edges: list[FlowEdge]) -> str:
+        join_id = self._find_join([edge.dst for edge in edges])
+        if join_id is None:
+            raise FlowExecutionError(f'Branches of fork "{fork_id}" never join')
+        return join_id
+
+    def _find_join(self, starts: list[str]) -> str | None:
+        common = set.intersection(*(reachable_from(self._flow.outgoing, s) for s in starts))
+        for step in self._flow.traverse():
+            if step.node.id in common:
+                return step.node.id
+        return None
+
+    async def _run_branch(self, edge: FlowEdge, join_id: str, context: FlowContext) -> bool:
+        """Follow a branch up to the join; returns False if it was stopped on the way."""
+        while edge.dst != join_id:
+            if self._stop_reason() is not None:
+                return False
+            self._transitions += 1
+            await self._enter(edge.src, edge.dst, context)
+            edges = self._flow.outgoing.get(edge.dst, [])
+            if self._is_fork(edge.dst, edges):
+                nested_join_id = await self._run_fork(edge.dst, edges, context)
+                if nested_join_id is None:
+                    return False
+                edge = FlowEdge(src=edge.dst, dst=nested_join_id, label=None)
+                continue
+            next_edge = self._choose_edge(edge.dst)
+            if next_edge is None:
+                raise FlowExecutionError(
+                    f'Node "{edge.dst}" needs a branch decision but no resolver is set'
+                )
+            edge = next_edge
+        return True
+
+    def _choose_edge(self, node_id: str) -> FlowEdge | None:
+        """Pick the edge to leave `node_id` by, taking a pending error branch first."""
+        if (label := self._error_branches.pop(node_id, None)) is not None:
+            edges = self._flow.outgoing.get(node_id, [])
+            return next(edge for edge in edges if edge.label == label)
+        return self._pick_edge(node_id)
+
+    def _pick_edge(self, node_id: str) -> FlowEdge | None:
+        """Pick the edge to leave `node_id` by, or `None` if a decision is needed."""
+        edges = self._flow.outgoing.get(node_id, [])
+        if not edges:
+            raise FlowExecutionError(f'Node "{node_id}" has no outgoing edges')
+        if len(edges) == 1:
+            return edges[0]
+        if self._resolver is None:
+            return None
+        choice = self._resolver(self._flow.nodes[node_id], edges)
+        for edge in edges:
+            if edge.label == choice:
+                return edge
+        for edge in edges:
+            if edge.dst == choice:
+                return edge
+        raise FlowExecutionError(
+            f'Branch resolver chose "{choice}", which is not a branch of "{node_id}"'
+        )
+
+    async def _enter(self, source_id: str, node_id: str, context: FlowContext) -> None:
+        entries = self._entries.get(node_id, 0) + 1
+        if self._loop_limit is not None and entries > self._loop_limit:
+            raise FlowLoopLimitError(node_id, self._loop_limit)
+        self._entries[node_id] = entries
+        context.node = self._flow.nodes[node_id]
+        self._visited.append(node_id)
+        if source_id:
+            self._taken.add((source_id, node_id))
+        for hook in self._transition_hooks:
+            hook(source_id, node_id)
+        handler = self._handlers.get(node_id)
+        entered_at = time.time()
+        started = time.perf_counter()
+        outcome: TraceOutcome = "ok"
+        try:
+            if handler is not None:
+                await self._call_with_retry(node_id, handler, context)
+            subflow = self._flow.nodes[node_id].metadata.get("subflow")
+            if isinstance(subflow, str):
+                await self._run_subflow(node_id, subflow, context)
+        except Exception as exc:
+            outcome = "timeout" if isinstance(exc, FlowNodeTimeoutError) else "err"
+            policy = self._error_policies.get(node_id, "abort")
+            if policy == "abort":
+                raise
+            logger.warning("Flow node {node_id} failed: {error}", node_id=node_id, error=exc)
+            if isinstance(policy, ErrorBranch):
+                self._error_branches[node_id] = policy.label
+        finally:
+            duration = time.perf_counter() - started
+            self._trace.append(TraceEntry(node_id, entered_at, duration, outcome))
+
+    async def _run_subflow(self, node_id: str, name: str, context: FlowContext) -> None:
+        stack = (*self._subflow_stack, name)
+        if name.casefold() in {entered.casefold() for entered in self._subflow_stack}:
+            raise FlowExecutionError(f"Sub-flow cycle: {' -> '.join(stack)}")
+        flow = self._subflow_resolver(name) if self._subflow_resolver is not None else None
+        if flow is None:
+            raise FlowExecutionError(f'Node "{node_id}" invokes unknown sub-flow "{name}"')
+        child = FlowExecutor(flow)
+        child._subflow_stack = stack
+        child._handlers = dict(self._subflow_handlers.get(name, {}))
+        child._subflow_handlers = self._subflow_handlers
+        child._subflow_resolver = self._subflow_resolver
+        child._resolver = self._resolver
+        child._node_timeout = self._node_timeout
+        child._max_steps = self._max_steps
+        child._loop_limit = self._loop_limit
+        child._merge = self._merge
+        child._transition_hooks = self._transition_hooks
+        child_context = FlowContext(
+            flow=flow, node=flow.nodes[flow.begin_id], bindings=copy.deepcopy(context.bindings)
+        )
+        await child.run_with(child_context, cancel_event=self._cancel_event)
+        if child_context.outcome is None or child_context.outcome.kind == "cancelled":
+            return
+        if child_context.outcome.kind != "done":
+            raise FlowExecutionError(
+                f'Sub-flow "{name}" stopped at "{child_context.outcome.node_id}" '
+                f"({child_context.outcome.kind})"
+            )
+        context.bindings.update(child_context.bindings)
+
+    async def _call_with_retry(
+        self, node_id: str, handler: NodeHandler, context: FlowContext
+    ) -> None:
+        policy = self._retries.get(node_id)
+        attempts = policy.attempts if policy is not None else 1
+        for attempt in range(1, attempts + 1):
+            try:
+                await self._call_handler(node_id, handler, context)
+                return
+            except Exception as exc:
+                if policy is None or attempt == attempts:
+                    raise
+                delay = policy.backoff * 2 ** (attempt - 1)
+                logger.debug(
+                    "Retrying flow node {node_id} in {delay}s: {error}",
+                    node_id=node_id,
+                    delay=delay,
+                    error=exc,
+                )
+                await asyncio.sleep(delay)
+
+    async def _call_handler(
+        self, node_id: str, handler: NodeHandler, context: FlowContext
+    ) -> None:
+        if self._node_timeout is None:
+            await handler(context)
+            return
+        try:
+            async with asyncio.timeout(self._node_timeout):
+                await handler(context)
+        except TimeoutError:
+            raise FlowNodeTimeoutError(node_id, self._node_timeout) from None
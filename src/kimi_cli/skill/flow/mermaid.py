@@ -1,10 +1,14 @@
 from __future__ import annotations
 
 import re
-from dataclasses import dataclass
+from collections.abc import Iterator
+from dataclasses import dataclass, replace
 
 from . import (
+    FLOW_DIRECTIONS,
     Flow,
+    FlowDiagnostic,
+    FlowDirection,
     FlowEdge,
     FlowNode,
     FlowNodeKind,
@@ -25,46 +29,179 @@ class _NodeDef:
     explicit: bool
 
 
-_NODE_ID_RE = re.compile(r"[A-Za-z0-9_][A-Za-z0-9_-]*")
-_HEADER_RE = re.compile(r"^(flowchart|graph)\b", re.IGNORECASE)
+# Hyphens are allowed inside ids but must not swallow the dashes of an arrow (`A-->B`).
+_NODE_ID_RE = re.compile(r"[A-Za-z0-9_](?:[A-Za-z0-9_]|-(?=[A-Za-z0-9_]))*")
+_HEADER_RE = re.compile(r"^(?:flowchart|graph)\b\s*([A-Za-z]*)", re.IGNORECASE)
+_EDGE_WEIGHT_RE = re.compile(r"^(-?\d+)\s*:\s*(.*)$", re.DOTALL)
+_STATE_HEADER_RE = re.compile(r"^stateDiagram(?:-v2)?\s*$", re.IGNORECASE)
+_STATE_REF = r"\[\*\]|[A-Za-z0-9_]+"
+_STATE_TRANSITION_RE = re.compile(
+    rf"^(?P<src>{_STATE_REF})\s*-->\s*(?P<dst>{_STATE_REF})(?:\s*:\s*(?P<label>.*))?$"
+)
+_STATE_ALIAS_RE = re.compile(r'^state\s+"(?P<label>[^"]*)"\s+as\s+(?P<id>[A-Za-z0-9_]+)$')
+_STATE_DECL_RE = re.compile(r"^state\s+(?P<id>[A-Za-z0-9_]+)(?:\s+<<\w+>>)?$")
+_STATE_DESCRIPTION_RE = re.compile(r"^(?P<id>[A-Za-z0-9_]+)\s*:\s*(?P<label>.+)$")
+_STATE_BEGIN_ID = "BEGIN"
+_STATE_END_ID = "END"
 
 _SHAPES = {
     "[": "]",
     "(": ")",
     "{": "}",
 }
-_PIPE_LABEL_RE = re.compile(r"\|([^|]*)\|")
-_EDGE_LABEL_RE = re.compile(r"--\s*([^>-][^>]*)\s*-->")
-_ARROW_RE = re.compile(r"[-.=]+>")
+_NODE_ID_HINT = 'node ids may only contain letters, digits, "_" and inner "-"'
+# `click A "url"` or `click A href "url"`, with an optional tooltip and target after the url.
+_CLICK_RE = re.compile(r'^click\s+(?P<id>[A-Za-z0-9_-]+)\s+(?:href\s+)?"(?P<url>[^"]+)"', re.I)
+# One hop of an edge chain: `-- text -->`, `-->`/`==>`/`-.->`, or an open `---`/`-.-` link,
+# optionally followed by a `|label|`.
+_LINK_RE = re.compile(
+    r"(?:--\s*(?P<text>[^\s>|-][^>|]*?)\s*-->|[-.=]+>|-{3,}|={3,}|-\.+-)"
+    r"(?:\s*\|(?P<pipe>[^|]*)\|)?"
+)
+
+
+def parse_mermaid_diagram(text: str) -> Flow:
+    """Parse a mermaid `flowchart`/`graph` or `stateDiagram-v2`, detected from its header."""
+    for _, raw_line in _diagram_lines(text):
+        line = _strip_comment(raw_line).strip()
+        if not line:
+            continue
+        if _STATE_HEADER_RE.match(line):
+            return parse_mermaid_state_diagram(text)
+        break
+    return parse_mermaid_flowchart(text)
 
 
 def parse_mermaid_flowchart(text: str) -> Flow:
     nodes: dict[str, _NodeDef] = {}
     outgoing: dict[str, list[FlowEdge]] = {}
+    direction: FlowDirection | None = None
+    diagnostics: list[FlowDiagnostic] = []
+    links: dict[str, str] = {}
 
-    for line_no, raw_line in enumerate(text.splitlines(), start=1):
-        line = _strip_comment(raw_line).strip()
-        if not line or line.startswith("%%"):
-            continue
-        if _HEADER_RE.match(line):
-            continue
-        if _is_style_line(line):
-            continue
-        line = _strip_style_tokens(line)
-
-        edge = _try_parse_edge_line(line, line_no)
-        if edge is not None:
-            src_spec, label, dst_spec = edge
-            src_node = _add_node(nodes, src_spec, line_no)
-            dst_node = _add_node(nodes, dst_spec, line_no)
-            flow_edge = FlowEdge(src=src_node.id, dst=dst_node.id, label=label)
-            outgoing.setdefault(flow_edge.src, []).append(flow_edge)
-            outgoing.setdefault(flow_edge.dst, [])
+    for line_no, raw_line in _diagram_lines(text):
+        if click := _CLICK_RE.match(_strip_comment(raw_line).strip()):
+            links[click.group("id")] = click.group("url")
             continue
+        try:
+            direction = _parse_line(raw_line, line_no, nodes, outgoing, direction, diagnostics)
+        except FlowParseError as exc:
+            if exc.line is not None:
+                raise
+            raise FlowParseError(exc.reason, line_no=line_no, line=raw_line.strip()) from None
+
+    flow_nodes = {node_id: node_def.node for node_id, node_def in nodes.items()}
+    for node_id in flow_nodes:
+        outgoing.setdefault(node_id, [])
+
+    flow_nodes = _infer_decision_nodes(flow_nodes, outgoing)
+    # Clicks on unknown nodes, like callback clicks, are simply dropped.
+    for node_id, url in links.items():
+        if node_id in flow_nodes:
+            flow_nodes[node_id] = replace(flow_nodes[node_id], link=url)
+    begin_id, end_id = validate_flow(flow_nodes, outgoing)
+    return Flow(
+        nodes=flow_nodes,
+        outgoing=outgoing,
+        begin_id=begin_id,
+        end_id=end_id,
+        direction=direction,
+        diagnostics=diagnostics,
+    )
+
+
+def _parse_line(
+    raw_line: str,
+    line_no: int,
+    nodes: dict[str, _NodeDef],
+    outgoing: dict[str, list[FlowEdge]],
+    direction: FlowDirection | None,
+    diagnostics: list[FlowDiagnostic],
+) -> FlowDirection | None:
+    """Add the nodes and edges declared on one line; returns the (possibly updated) direction."""
+    line = _strip_comment(raw_line).strip()
+    if not line or line.startswith("%%"):
+        return direction
+    if header := _HEADER_RE.match(line):
+        return _parse_direction(header.group(1), line_no)
+    if _is_style_line(line):
+        return direction
+    line = _strip_style_tokens(line)
+
+    hops = _try_parse_edge_line(line, line_no)
+    if hops is not None:
+        _check_line_end(line, hops[-1][3], hops[-1][2][-1].node_id, line_no)
+        for src_specs, label, dst_specs, _ in hops:
+            weight, label = _split_edge_weight(label)
+            for src_spec in src_specs:
+                src_node = _add_node(nodes, src_spec, line_no, diagnostics)
+                for dst_spec in dst_specs:
+                    dst_node = _add_node(nodes, dst_spec, line_no, diagnostics)
+                    flow_edge = FlowEdge(
+                        src=src_node.id, dst=dst_node.id, label=label, weight=weight
+                    )
+                    outgoing.setdefault(flow_edge.src, []).append(flow_edge)
+                    outgoing.setdefault(flow_edge.dst, [])
+        return direction
+
+    parsed = _try_parse_node_line(line, line_no)
+    if parsed is not None:
+        node_specs, end = parsed
+        _check_line_end(line, end, node_specs[-1].node_id, line_no)
+        for node_spec in node_specs:
+            _add_node(nodes, node_spec, line_no, diagnostics)
+    elif _LINK_RE.search(_mask_quoted(line)):
+        token = line.split(None, 1)[0]
+        raise FlowParseError(f'Invalid node id "{token}"; {_NODE_ID_HINT}', line_no=line_no)
+    return direction
+
+
+def _check_line_end(line: str, idx: int, node_id: str, line_no: int) -> None:
+    """Reject leftovers after the last node of a line, e.g. the `B` of `A B --> C`."""
+    rest = line[idx:].strip().removesuffix(";").strip()
+    if rest:
+        token = rest.split(None, 1)[0]
+        raise FlowParseError(
+            f'Unexpected "{token}" after node id "{node_id}"; {_NODE_ID_HINT}', line_no=line_no
+        )
 
-        node_spec = _try_parse_node_line(line, line_no)
-        if node_spec is not None:
-            _add_node(nodes, node_spec, line_no)
+
+def parse_mermaid_state_diagram(text: str) -> Flow:
+    """
+    Parse a mermaid `stateDiagram-v2` into a flow.
+
+    `[*]` maps to a `BEGIN` node when used as a source and to an `END` node as a target.
+    Transition text after `:` becomes the edge label; `A : text` and `state "text" as A`
+    set node labels. Composite states are not supported.
+    """
+    nodes: dict[str, _NodeDef] = {}
+    outgoing: dict[str, list[FlowEdge]] = {}
+    direction: FlowDirection | None = None
+    diagnostics: list[FlowDiagnostic] = []
+    in_note = False
+
+    for line_no, raw_line in _diagram_lines(text):
+        line = _strip_comment(raw_line).strip()
+        if in_note:
+            in_note = line.lower() != "end note"
+            continue
+        try:
+            if not line or _STATE_HEADER_RE.match(line):
+                continue
+            lowered = line.lower()
+            if lowered.startswith("direction "):
+                direction = _parse_direction(line.split(None, 1)[1], line_no)
+                continue
+            if lowered.startswith("note "):
+                in_note = ":" not in line
+                continue
+            if _is_style_line(line):
+                continue
+            _parse_state_line(line, line_no, nodes, outgoing, diagnostics)
+        except FlowParseError as exc:
+            if exc.line is not None:
+                raise
+            raise FlowParseError(exc.reason, line_no=line_no, line=raw_line.strip()) from None
 
     flow_nodes = {node_id: node_def.node for node_id, node_def in nodes.items()}
     for node_id in flow_nodes:
@@ -72,40 +209,102 @@ def parse_mermaid_flowchart(text: str) -> Flow:
 
     flow_nodes = _infer_decision_nodes(flow_nodes, outgoing)
     begin_id, end_id = validate_flow(flow_nodes, outgoing)
-    return Flow(nodes=flow_nodes, outgoing=outgoing, begin_id=begin_id, end_id=end_id)
+    return Flow(
+        nodes=flow_nodes,
+        outgoing=outgoing,
+        begin_id=begin_id,
+        end_id=end_id,
+        direction=direction,
+        diagnostics=diagnostics,
+    )
 
 
-def _try_parse_edge_line(line: str, line_no: int) -> tuple[_NodeSpec, str | None, _NodeSpec] | None:
-    try:
-        src_spec, idx = _parse_node_token(line, 0, line_no)
-    except FlowParseError:
+def _parse_state_line(
+    line: str,
+    line_no: int,
+    nodes: dict[str, _NodeDef],
+    outgoing: dict[str, list[FlowEdge]],
+    diagnostics: list[FlowDiagnostic],
+) -> None:
+    if line.endswith("{"):
+        raise FlowParseError("Composite states are not supported", line_no=line_no)
+    if match := _STATE_TRANSITION_RE.match(line):
+        src_id = _STATE_BEGIN_ID if match.group("src") == "[*]" else match.group("src")
+        dst_id = _STATE_END_ID if match.group("dst") == "[*]" else match.group("dst")
+        src = _add_node(nodes, _NodeSpec(node_id=src_id, label=None), line_no, diagnostics)
+        dst = _add_node(nodes, _NodeSpec(node_id=dst_id, label=None), line_no, diagnostics)
+        weight, label = _split_edge_weight((match.group("label") or "").strip() or None)
+        edge = FlowEdge(src=src.id, dst=dst.id, label=label, weight=weight)
+        outgoing.setdefault(src.id, []).append(edge)
+        outgoing.setdefault(dst.id, [])
+        return
+    if match := _STATE_ALIAS_RE.match(line) or _STATE_DESCRIPTION_RE.match(line):
+        spec = _NodeSpec(node_id=match.group("id"), label=match.group("label").strip())
+        _add_node(nodes, spec, line_no, diagnostics)
+        return
+    if match := _STATE_DECL_RE.match(line):
+        _add_node(nodes, _NodeSpec(node_id=match.group("id"), label=None), line_no, diagnostics)
+        return
+    raise FlowParseError("Unsupported state diagram statement", line_no=line_no)
+
+
+def _parse_direction(token: str, line_no: int) -> FlowDirection | None:
+    if not token:
         return None
+    for direction in FLOW_DIRECTIONS:
+        if token.upper() == direction:
+            return direction
+    raise FlowParseError(f'Unknown flowchart direction "{token}"', line_no=line_no)
 
-    normalized, label = _normalize_edge_line(line)
-    idx = _skip_ws(normalized, idx)
-    if ">" not in normalized[idx:]:
-        if "---" not in normalized[idx:]:
-            return None
-        normalized = normalized[:idx] + normalized[idx:].replace("---", "-->", 1)
 
-    normalized = _ARROW_RE.sub("-->", normalized)
-    arrow_idx = normalized.rfind(">")
-    if arrow_idx == -1:
-        return None
+def _try_parse_edge_line(
+    line: str, line_no: int
+) -> list[tuple[list[_NodeSpec], str | None, list[_NodeSpec], int]] | None:
+    """
+    Parse an edge line into its hops, expanding chains like `A --> B --> C`.
 
-    dst_start = _skip_ws(normalized, arrow_idx + 1)
+    Either end of a hop may be a node list like `B & C`, linking every source to every
+    target. Each hop also carries the index just past its targets.
+    """
     try:
-        dst_spec, _ = _parse_node_token(normalized, dst_start, line_no)
+        src_spec, idx = _parse_node_group(line, 0, line_no)
     except FlowParseError:
         return None
 
-    return src_spec, label, dst_spec
+    masked = _mask_quoted(line)
+    hops: list[tuple[list[_NodeSpec], str | None, list[_NodeSpec], int]] = []
+    while True:
+        idx = _skip_ws(line, idx)
+        link = _LINK_RE.match(masked, idx)
+        if link is None:
+            break
+        label = None
+        for group in ("pipe", "text"):
+            if link.group(group) is not None:
+                label = _edge_label_text(line[link.start(group) : link.end(group)])
+                break
+        # Past an arrow the line can only be an edge, so a bad target is a real error.
+        dst_spec, idx = _parse_node_group(line, _skip_ws(line, link.end()), line_no)
+        hops.append((src_spec, label, dst_spec, idx))
+        src_spec = dst_spec
+
+    return hops or None
+
+
+def _parse_node_group(line: str, idx: int, line_no: int) -> tuple[list[_NodeSpec], int]:
+    """Parse a node, or a `&`-separated list of nodes such as `B & C[Right]`."""
+    spec, idx = _parse_node_token(line, idx, line_no)
+    specs = [spec]
+    while (next_idx := _skip_ws(line, idx)) < len(line) and line[next_idx] == "&":
+        spec, idx = _parse_node_token(line, _skip_ws(line, next_idx + 1), line_no)
+        specs.append(spec)
+    return specs, idx
 
 
 def _parse_node_token(line: str, idx: int, line_no: int) -> tuple[_NodeSpec, int]:
     match = _NODE_ID_RE.match(line, idx)
     if not match:
-        raise FlowParseError(_line_error(line_no, "Expected node id"))
+        raise FlowParseError("Expected node id", line_no=line_no)
     node_id = match.group(0)
     idx = match.end()
 
@@ -120,13 +319,13 @@ def _parse_node_token(line: str, idx: int, line_no: int) -> tuple[_NodeSpec, int
 
 def _parse_label(line: str, idx: int, close_char: str, line_no: int) -> tuple[str, int]:
     if idx >= len(line):
-        raise FlowParseError(_line_error(line_no, "Expected node label"))
+        raise FlowParseError("Expected node label", line_no=line_no)
     if close_char == ")" and line[idx] == "[":
         label, idx = _parse_label(line, idx + 1, "]", line_no)
         while idx < len(line) and line[idx].isspace():
             idx += 1
         if idx >= len(line) or line[idx] != ")":
-            raise FlowParseError(_line_error(line_no, "Unclosed node label"))
+            raise FlowParseError("Unclosed node label", line_no=line_no)
         return label, idx + 1
     if line[idx] == '"':
         idx += 1
@@ -138,7 +337,7 @@ def _parse_label(line: str, idx: int, close_char: str, line_no: int) -> tuple[st
                 while idx < len(line) and line[idx].isspace():
                     idx += 1
                 if idx >= len(line) or line[idx] != close_char:
-                    raise FlowParseError(_line_error(line_no, "Unclosed node label"))
+                    raise FlowParseError("Unclosed node label", line_no=line_no)
                 return "".join(buf), idx + 1
             if ch == "\\" and idx + 1 < len(line):
                 buf.append(line[idx + 1])
@@ -146,14 +345,14 @@ def _parse_label(line: str, idx: int, close_char: str, line_no: int) -> tuple[st
                 continue
             buf.append(ch)
             idx += 1
-        raise FlowParseError(_line_error(line_no, "Unclosed quoted label"))
+        raise FlowParseError("Unclosed quoted label", line_no=line_no)
 
     end = line.find(close_char, idx)
     if end == -1:
-        raise FlowParseError(_line_error(line_no, "Unclosed node label"))
+        raise FlowParseError("Unclosed node label", line_no=line_no)
     label = line[idx:end].strip()
     if not label:
-        raise FlowParseError(_line_error(line_no, "Node label cannot be empty"))
+        raise FlowParseError("Node label cannot be empty", line_no=line_no)
     return label, end + 1
 
 
@@ -163,11 +362,16 @@ def _skip_ws(line: str, idx: int) -> int:
     return idx
 
 
-def _add_node(nodes: dict[str, _NodeDef], spec: _NodeSpec, line_no: int) -> FlowNode:
+def _add_node(
+    nodes: dict[str, _NodeDef],
+    spec: _NodeSpec,
+    line_no: int,
+    diagnostics: list[FlowDiagnostic],
+) -> FlowNode:
     label = spec.label if spec.label is not None else spec.node_id
     label_norm = label.strip().lower()
     if not label:
-        raise FlowParseError(_line_error(line_no, "Node label cannot be empty"))
+        raise FlowParseError("Node label cannot be empty", line_no=line_no)
 
     kind: FlowNodeKind = "task"
     if label_norm == "begin":
@@ -193,11 +397,33 @@ def _add_node(nodes: dict[str, _NodeDef], spec: _NodeSpec, line_no: int) -> Flow
         nodes[spec.node_id] = _NodeDef(node=node, explicit=True)
         return node
 
-    raise FlowParseError(_line_error(line_no, f'Conflicting definition for node "{spec.node_id}"'))
+    # Both definitions are explicit and differ: keep the first and record the conflict.
+    diagnostics.append(
+        FlowDiagnostic(
+            message=(
+                f'Node "{spec.node_id}" is redefined as "{label}"; '
+                f'keeping "{existing.node.label_text}"'
+            ),
+            line_no=line_no,
+        )
+    )
+    return existing.node
 
 
-def _line_error(line_no: int, message: str) -> str:
-    return f"Line {line_no}: {message}"
+def _diagram_lines(text: str) -> Iterator[tuple[int, str]]:
+    """
+    Yield numbered lines, skipping `%%{init: ...}%%` directives even when they span lines.
+
+    Directives only theme the rendered diagram, so they are dropped rather than parsed.
+    """
+    in_directive = False
+    for line_no, raw_line in enumerate(text.splitlines(), start=1):
+        if not in_directive and raw_line.strip().startswith("%%{"):
+            in_directive = True
+        if in_directive:
+            in_directive = "}%%" not in raw_line
+            continue
+        yield line_no, raw_line
 
 
 def _strip_comment(line: str) -> str:
@@ -227,27 +453,48 @@ def _strip_style_tokens(line: str) -> str:
     return re.sub(r":::[A-Za-z0-9_-]+", "", line)
 
 
-def _try_parse_node_line(line: str, line_no: int) -> _NodeSpec | None:
+def _try_parse_node_line(line: str, line_no: int) -> tuple[list[_NodeSpec], int] | None:
     try:
-        node_spec, _ = _parse_node_token(line, 0, line_no)
+        return _parse_node_group(line, 0, line_no)
     except FlowParseError:
         return None
-    return node_spec
 
 
-def _normalize_edge_line(line: str) -> tuple[str, str | None]:
-    label = None
-    normalized = line
-    pipe_match = _PIPE_LABEL_RE.search(normalized)
-    if pipe_match:
-        label = pipe_match.group(1).strip() or None
-        normalized = normalized[: pipe_match.start()] + normalized[pipe_match.end() :]
-    if label is None:
-        edge_match = _EDGE_LABEL_RE.search(normalized)
-        if edge_match:
-            label = edge_match.group(1).strip() or None
-            normalized = normalized[: edge_match.start()] + "-->" + normalized[edge_match.end() :]
-    return normalized, label
+def _edge_label_text(text: str) -> str | None:
+    text = text.strip()
+    if len(text) >= 2 and text[0] == text[-1] == '"':
+        text = text[1:-1].strip()
+    return text or None
+
+
+def _split_edge_weight(label: str | None) -> tuple[int | None, str | None]:
+    """Split a `1: yes` label into its weight and remaining text."""
+    if label is None or not (match := _EDGE_WEIGHT_RE.match(label)):
+        return None, label
+    return int(match.group(1)), match.group(2).strip() or None
+
+
+def _mask_quoted(text: str) -> str:
+    """Blank out the contents of quoted labels so structural scans ignore them."""
+    out: list[str] = []
+    in_quote = False
+    escape = False
+    for ch in text:
+        if not in_quote:
+            in_quote = ch == '"'
+            out.append(ch)
+        elif escape:
+            escape = False
+            out.append("\0")
+        elif ch == "\\":
+            escape = True
+            out.append("\0")
+        elif ch == '"':
+            in_quote = False
+            out.append(ch)
+        else:
+            out.append("\0")
+    return "".join(out)
 
 
 def _infer_decision_nodes(
@@ -257,7 +504,9 @@ def _infer_decision_nodes(
     updated: dict[str, FlowNode] = {}
     for node_id, node in nodes.items():
         kind = node.kind
-        if kind == "task" and len(outgoing.get(node_id, [])) > 1:
+        edges = outgoing.get(node_id, [])
+        # A task fanning out along unlabeled edges only is a fork, not a decision.
+        if kind == "task" and len(edges) > 1 and any(edge.label for edge in edges):
             kind = "decision"
         if kind != node.kind:
             updated[node_id] = FlowNode(id=node.id, label=node.label, kind=kind)
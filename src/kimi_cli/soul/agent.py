@@ -19,7 +19,12 @@ from kimi_cli.config import Config
 from kimi_cli.exception import MCPConfigError, SystemPromptTemplateError
 from kimi_cli.llm import LLM
 from kimi_cli.session import Session
-from kimi_cli.skill import Skill, discover_skills_from_roots, index_skills, resolve_skills_roots
+from kimi_cli.skill import (
+    Skill,
+    discover_applicable_skills,
+    index_skills,
+    resolve_skills_roots,
+)
 from kimi_cli.soul.approval import Approval
 from kimi_cli.soul.denwarenji import DenwaRenji
 from kimi_cli.soul.toolset import KimiToolset
@@ -92,7 +97,7 @@ class Runtime:
 
         # Discover and format skills
         skills_roots = await resolve_skills_roots(session.work_dir, skills_dir_override=skills_dir)
-        skills = await discover_skills_from_roots(skills_roots)
+        skills = await discover_applicable_skills(skills_roots, session.work_dir)
         skills_by_name = index_skills(skills)
         logger.info("Discovered {count} skill(s)", count=len(skills))
         skills_formatted = "\n".join(
@@ -233,15 +233,21 @@ class KimiSoul:
                     name=name,
                 )
                 continue
+            aliases = [
+                alias_name
+                for alias in skill.aliases
+                if (alias_name := f"{SKILL_COMMAND_PREFIX}{alias}") not in seen_names
+            ]
             commands.append(
                 SlashCommand(
                     name=name,
                     func=self._make_skill_runner(skill),
                     description=skill.description or "",
-                    aliases=[],
+                    aliases=aliases,
                 )
             )
             seen_names.add(name)
+            seen_names.update(aliases)
 
         for skill in self._runtime.skills.values():
             if skill.type != "flow":
@@ -249,6 +255,14 @@ class KimiSoul:
             if skill.flow is None:
                 logger.warning("Flow skill {name} has no flow; skipping", name=skill.name)
                 continue
+            if (fork_id := FlowRunner.find_fork(skill.flow)) is not None:
+                logger.warning(
+                    'Flow skill {name} forks at node "{node_id}", which agent flows cannot run; '
+                    "skipping",
+                    name=skill.name,
+                    node_id=fork_id,
+                )
+                continue
             command_name = f"{FLOW_COMMAND_PREFIX}{skill.name}"
             if command_name in seen_names:
                 logger.warning(
@@ -591,6 +605,19 @@ class FlowRunner:
         max_moves = total_runs
         return FlowRunner(flow, max_moves=max_moves)
 
+    @staticmethod
+    def find_fork(flow: Flow) -> str | None:
+        """
+        Return the first node that fans out without being a decision, or None.
+
+        Agent flows follow one edge per node, so such a node would silently run only its
+        first branch; `FlowExecutor` can run it in parallel instead.
+        """
+        for node_id, edges in flow.outgoing.items():
+            if len(edges) > 1 and flow.nodes[node_id].kind != "decision":
+                return node_id
+        return None
+
     async def run(self, soul: KimiSoul, args: str) -> None:
         if args.strip():
             command = f"/{FLOW_COMMAND_PREFIX}{self._name}" if self._name else "/flow"
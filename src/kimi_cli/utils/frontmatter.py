@@ -1,5 +1,6 @@
 from __future__ import annotations
 
+import tomllib
 from pathlib import Path
 from typing import Any, cast
 
@@ -8,27 +9,99 @@ import yaml
 
 def parse_frontmatter(text: str) -> dict[str, Any] | None:
     """
-    Parse YAML frontmatter from a text blob.
+    Parse YAML (`---`) or TOML (`+++`) frontmatter from a text blob.
 
     Raises:
-        ValueError: If the frontmatter YAML is invalid.
+        ValueError: If the frontmatter YAML or TOML is invalid.
     """
+    frontmatter, _ = split_frontmatter(text)
+    return frontmatter
+
+
+def split_frontmatter(text: str) -> tuple[dict[str, Any] | None, str]:
+    """
+    Split a text blob into its parsed frontmatter and the body that follows it.
+
+    The delimiter on the first line selects the format: `---` for YAML, `+++` for TOML. The
+    frontmatter is `None` when the text does not start with a fenced block or the block is
+    empty. The body is everything after the closing fence, or the whole text when
+    there is no complete frontmatter block.
+
+    Raises:
+        ValueError: If the frontmatter YAML or TOML is invalid.
+    """
+    delimiter, frontmatter, body = _split_raw(text)
+    if not frontmatter:
+        return None, body
+    if delimiter == "+++":
+        return _load_toml(frontmatter), body
+    return _load_yaml(frontmatter), body
+
+
+def render_frontmatter(data: dict[str, Any], body: str) -> str:
+    """Render `data` as YAML frontmatter followed by `body`, the inverse of `split_frontmatter`."""
+    if not data:
+        return body.strip() + "\n"
+    dumped = yaml.safe_dump(data, sort_keys=False, allow_unicode=True)
+    return f"---\n{dumped}---\n\n{body.strip()}\n"
+
+
+def split_frontmatter_lenient(text: str) -> tuple[dict[str, Any] | None, str, list[str]]:
+    """
+    Like `split_frontmatter`, but recover what it can from invalid YAML frontmatter.
+
+    When the YAML block does not parse as a whole, each top-level key is parsed on its own
+    (together with its indented continuation lines). Keys that still fail are left out and
+    returned as the third element, in order; it is empty when the block parsed normally.
+
+    Raises:
+        ValueError: If the frontmatter TOML is invalid.
+    """
+    delimiter, frontmatter, body = _split_raw(text)
+    if not frontmatter:
+        return None, body, []
+    if delimiter == "+++":
+        return _load_toml(frontmatter), body, []
+    try:
+        return _load_yaml(frontmatter), body, []
+    except ValueError:
+        pass
+
+    data: dict[str, Any] = {}
+    broken: list[str] = []
+    for key, chunk in _split_yaml_keys(frontmatter):
+        try:
+            data.update(_load_yaml(chunk))
+        except ValueError:
+            broken.append(key)
+    return data, body, broken
+
+
+def _split_raw(text: str) -> tuple[str, str, str]:
+    """Return the delimiter, the stripped frontmatter block and the body; the block may be ""."""
     lines = text.splitlines()
-    if not lines or lines[0].strip() != "---":
-        return None
+    delimiter = lines[0].strip() if lines else ""
+    if delimiter not in ("---", "+++"):
+        return "", "", text
 
-    frontmatter_lines: list[str] = []
-    for line in lines[1:]:
-        if line.strip() == "---":
+    for idx, line in enumerate(lines[1:], start=1):
+        if line.strip() == delimiter:
             break
-        frontmatter_lines.append(line)
     else:
-        return None
+        return "", "", text
+
+    body = "\n".join(lines[idx + 1 :])
+    return delimiter, "\n".join(lines[1:idx]).strip(), body
 
-    frontmatter = "\n".join(frontmatter_lines).strip()
-    if not frontmatter:
-        return None
 
+def _load_toml(frontmatter: str) -> dict[str, Any]:
+    try:
+        return tomllib.loads(frontmatter)
+    except tomllib.TOMLDecodeError as exc:
+        raise ValueError("Invalid frontmatter TOML.") from exc
+
+
+def _load_yaml(frontmatter: str) -> dict[str, Any]:
     try:
         raw_data: Any = yaml.safe_load(frontmatter)
     except yaml.YAMLError as exc:
@@ -40,11 +113,28 @@ def parse_frontmatter(text: str) -> dict[str, Any] | None:
     return cast(dict[str, Any], raw_data)
 
 
+def _split_yaml_keys(frontmatter: str) -> list[tuple[str, str]]:
+    """Group YAML lines into `(key, text)` chunks, one per top-level key."""
+    chunks: list[tuple[str, list[str]]] = []
+    for line in frontmatter.splitlines():
+        starts_key = line[:1] not in ("", " ", "\t", "-", "#")
+        if not chunks and line.startswith("#"):
+            continue
+        if starts_key or not chunks:
+            chunks.append((line.split(":", 1)[0].strip(), [line]))
+        else:
+            chunks[-1][1].append(line)
+    return [(key, "\n".join(lines)) for key, lines in chunks]
+
+
 def read_frontmatter(path: Path) -> dict[str, Any] | None:
     """
-    Read the YAML frontmatter at the start of a file.
+    Read the YAML or TOML frontmatter at the start of a file; see `split_frontmatter`.
 
     Args:
         path: Path to an existing file that may contain frontmatter.
+
+    Raises:
+        ValueError: If the frontmatter YAML or TOML is invalid.
     """
     return parse_frontmatter(path.read_text(encoding="utf-8", errors="replace"))
@@ -1,11 +1,24 @@
 from __future__ import annotations
 
+import json
+
 import pytest
 from inline_snapshot import snapshot
 
-from kimi_cli.skill.flow import Flow, FlowParseError, FlowValidationError, parse_choice
+from kimi_cli.skill.flow import (
+    EndUnreachableError,
+    Flow,
+    FlowDiagnostic,
+    FlowEdge,
+    FlowParseError,
+    FlowValidationError,
+    MissingBeginError,
+    MissingEndError,
+    MultipleBeginError,
+    parse_choice,
+)
 from kimi_cli.skill.flow.d2 import parse_d2_flowchart
-from kimi_cli.skill.flow.mermaid import parse_mermaid_flowchart
+from kimi_cli.skill.flow.mermaid import parse_mermaid_diagram, parse_mermaid_flowchart
 
 
 def test_parse_flowchart_basic() -> None:
@@ -102,6 +115,87 @@ def test_parse_flowchart_quoted_label() -> None:
     )
 
 
+def test_parse_flowchart_quoted_label_with_structure_chars() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                'S([BEGIN]) --> A["Fetch [raw] data | v2"]',
+                'A -->|"ok | done"| B["a | b | c --> d"]',
+                "A -->|retry| S",
+                "B --> E([END])",
+            ]
+        )
+    )
+
+    assert flow.nodes["A"].label == "Fetch [raw] data | v2"
+    assert flow.nodes["B"].label == "a | b | c --> d"
+    assert flow.branches_of("A") == [("ok | done", "B"), ("retry", "S")]
+
+
+def test_parse_flowchart_chained_edges() -> None:
+    expanded = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B[Plan]",
+                "B --> my-step[Build]",
+                "my-step -->|done| C{Ship?}",
+                "C -->|yes| D([END])",
+                "C -->|no| B",
+            ]
+        )
+    )
+    chained = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B[Plan] --> my-step[Build] -->|done| C{Ship?}",
+                "C -->|yes| D([END])",
+                "C-->|no|B",
+            ]
+        )
+    )
+
+    assert chained == expanded
+
+
+def test_parse_flowchart_self_loop() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "S([BEGIN]) --> A[Call API]",
+                "A -->|retry| A",
+                "A -->|ok| E([END])",
+            ]
+        )
+    )
+
+    assert flow.branches_of("A") == [("retry", "A"), ("ok", "E")]
+    assert flow.successors("A") == ["A", "E"]
+    assert flow.unreachable_nodes() == []
+
+
+def test_parse_flowchart_duplicate_definition_keeps_first() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "S([BEGIN]) --> A[First]",
+                "A --> E([END])",
+                "A[Second]",
+            ]
+        )
+    )
+
+    assert flow.nodes["A"].label == "First"
+    assert flow.successors("S") == ["A"]
+    assert flow.diagnostics == [
+        FlowDiagnostic(message='Node "A" is redefined as "Second"; keeping "First"', line_no=4)
+    ]
+
+
 def test_parse_flowchart_multi_edges_require_labels() -> None:
     with pytest.raises(FlowValidationError):
         parse_mermaid_flowchart(
@@ -116,6 +210,134 @@ def test_parse_flowchart_multi_edges_require_labels() -> None:
         )
 
 
+def test_parse_flowchart_reports_fallback_reason() -> None:
+    with pytest.raises(MissingBeginError):
+        parse_mermaid_flowchart("flowchart TD\nA --> B")
+    with pytest.raises(MissingEndError):
+        parse_mermaid_flowchart("flowchart TD\nA([BEGIN]) --> B")
+    with pytest.raises(EndUnreachableError):
+        parse_mermaid_flowchart("flowchart TD\nA([BEGIN]) --> B\nC --> D([END])")
+
+
+def test_parse_flowchart_rejects_multiple_begin_nodes() -> None:
+    with pytest.raises(MultipleBeginError) as exc_info:
+        parse_mermaid_flowchart("flowchart TD\nA([BEGIN]) --> C([END])\nB([BEGIN]) --> C")
+
+    assert exc_info.value.node_ids == ["A", "B"]
+    assert str(exc_info.value) == snapshot('Expected exactly one BEGIN node, found 2: "A", "B"')
+
+
+def test_parse_flowchart_reports_malformed_edge_line() -> None:
+    with pytest.raises(FlowParseError) as exc_info:
+        parse_mermaid_flowchart(
+            "\n".join(
+                [
+                    "flowchart TD",
+                    "A([BEGIN]) --> B[Work]",
+                    "  B --> [Oops]",
+                    "B --> C([END])",
+                ]
+            )
+        )
+
+    assert exc_info.value.line_no == 3
+    assert exc_info.value.line == "B --> [Oops]"
+    assert str(exc_info.value) == snapshot("Line 3: Expected node id: B --> [Oops]")
+
+
+@pytest.mark.parametrize(
+    ("line", "reason"),
+    [
+        ("A B --> C([END])", 'Unexpected "B" after node id "A"'),
+        ("A --> C@1([END])", 'Unexpected "@1([END])" after node id "C"'),
+        ("$A --> C([END])", 'Invalid node id "$A"'),
+    ],
+)
+def test_parse_flowchart_rejects_invalid_node_ids(line: str, reason: str) -> None:
+    with pytest.raises(FlowParseError) as exc_info:
+        parse_mermaid_flowchart("\n".join(["flowchart TD", "BEGIN([BEGIN]) --> A", line]))
+
+    assert exc_info.value.line_no == 3
+    assert exc_info.value.line == line
+    assert exc_info.value.reason == (
+        f'{reason}; node ids may only contain letters, digits, "_" and inner "-"'
+    )
+
+
+def test_parse_flowchart_allows_trailing_semicolons() -> None:
+    flow = parse_mermaid_flowchart("flowchart TD\nBEGIN([BEGIN]) --> A[Work];\nA --> END([END]);")
+
+    assert list(flow.nodes) == ["BEGIN", "A", "END"]
+
+
+def test_parse_flowchart_expands_node_lists() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Split] --> B[Left] & C[Right]",
+                "B & C -->|done| D",
+                "D & E",
+                "D --> END([END])",
+            ]
+        )
+    )
+
+    assert [(edge.dst, edge.label) for edge in flow.outgoing["A"]] == [("B", None), ("C", None)]
+    assert [(edge.src, edge.dst, edge.label) for edge in flow.outgoing["C"]] == [
+        ("C", "D", "done")
+    ]
+    assert flow.nodes["C"].label == "Right"
+    assert "E" in flow.nodes
+
+
+def test_parse_state_diagram() -> None:
+    flow = parse_mermaid_diagram(
+        "\n".join(
+            [
+                "stateDiagram-v2",
+                "    %% same shape as the basic flowchart",
+                "    [*] --> Search",
+                '    state "Search stdrc" as Search',
+                "    Search --> Check",
+                "    Check : Enough?",
+                "    Check --> [*] : yes",
+                "    Check --> Search : no",
+            ]
+        )
+    )
+
+    assert _flow_snapshot(flow) == snapshot(
+        {
+            "begin_id": "BEGIN",
+            "end_id": "END",
+            "nodes": {
+                "BEGIN": {"kind": "begin", "label": "BEGIN"},
+                "Search": {"kind": "task", "label": "Search stdrc"},
+                "Check": {"kind": "decision", "label": "Enough?"},
+                "END": {"kind": "end", "label": "END"},
+            },
+            "outgoing": {
+                "BEGIN": [{"dst": "Search", "label": None}],
+                "Search": [{"dst": "Check", "label": None}],
+                "Check": [
+                    {"dst": "END", "label": "yes"},
+                    {"dst": "Search", "label": "no"},
+                ],
+                "END": [],
+            },
+        }
+    )
+
+
+def test_parse_mermaid_diagram_keeps_flowcharts() -> None:
+    text = "flowchart TD\nA([BEGIN]) --> B[Work]\nB --> C([END])"
+
+    assert _flow_snapshot(parse_mermaid_diagram(text)) == _flow_snapshot(
+        parse_mermaid_flowchart(text)
+    )
+
+
 def test_parse_d2_flowchart_typical_example() -> None:
     flow = parse_d2_flowchart(
         "\n".join(
@@ -352,6 +574,308 @@ def test_parse_flowchart_ignores_style_and_shapes() -> None:
     )
 
 
+def test_flow_branches_of_decision_node() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B{Tests pass?}",
+                "B -->|yes| C([END])",
+                "B -->|no| D[Fix tests]",
+                "D --> B",
+            ]
+        )
+    )
+
+    assert flow.nodes["B"].kind == "decision"
+    assert flow.branches_of("B") == [("yes", "C"), ("no", "D")]
+    assert flow.branches_of("C") == []
+
+
+def test_flow_fork_join_adjacency() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Split work]",
+                "A --> B[Left half]",
+                "A --> C[Right half]",
+                "B --> D[Merge]",
+                "C --> D",
+                "D --> END([END])",
+            ]
+        )
+    )
+
+    assert flow.nodes["A"].kind == "task"
+    assert flow.successors("A") == ["B", "C"]
+    assert flow.predecessors("D") == ["B", "C"]
+    assert flow.successors("END") == []
+    assert flow.predecessors("BEGIN") == []
+
+    d2_flow = parse_d2_flowchart("BEGIN -> A\nA -> B\nA -> C\nB -> D\nC -> D\nD -> END")
+    assert d2_flow.nodes["A"].kind == "task"
+    assert d2_flow.predecessors("D") == ["B", "C"]
+
+
+def test_flow_traverse_orders_nodes_and_exposes_loops() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B[Search stdrc]",
+                "B --> C{Enough?}",
+                "C -->|yes| D[Summarize]",
+                "C -->|no| B",
+                "D --> E([END])",
+            ]
+        )
+    )
+
+    steps = list(flow.traverse())
+
+    assert [step.node.id for step in steps] == ["A", "B", "C", "D", "E"]
+    assert steps[2].branches == [("yes", "D"), ("no", "B")]
+    assert steps[2].back_edges == [FlowEdge(src="C", dst="B", label="no")]
+    assert all(not step.back_edges for step in steps if step.node.id != "C")
+
+
+def test_flow_cycles() -> None:
+    looping = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "S([BEGIN]) --> A[Draft]",
+                "A --> B{Good?}",
+                "B -->|no| A",
+                "B -->|yes| C[Polish]",
+                "C -->|again| C",
+                "C -->|done| E([END])",
+            ]
+        )
+    )
+    linear = parse_mermaid_flowchart("flowchart TD\nA([BEGIN]) --> B[Work]\nB --> C([END])")
+
+    assert looping.cycles() == [["A", "B"], ["C"]]
+    assert not looping.is_acyclic()
+    assert linear.cycles() == []
+    assert linear.is_acyclic()
+
+
+def test_parse_flowchart_ignores_comment_lines() -> None:
+    plain = [
+        "flowchart TD",
+        "A([BEGIN]) --> B[Work]",
+        "B --> C([END])",
+    ]
+    commented = [
+        "%% leading comment",
+        "flowchart TD",
+        "A([BEGIN]) --> B[Work]",
+        "  %% explanatory comment between edges",
+        "B --> C([END])",
+        "%% C --> D[Phantom]",
+    ]
+
+    assert parse_mermaid_flowchart("\n".join(commented)) == parse_mermaid_flowchart(
+        "\n".join(plain)
+    )
+
+
+def test_parse_flowchart_styling_keeps_graph_shape() -> None:
+    plain = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B[Work]",
+                "B --> C([END])",
+            ]
+        )
+    )
+    styled = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "classDef important fill:#f9f,stroke:#333",
+                "A([BEGIN]) --> B[Work]:::important",
+                "style A fill:#f9f",
+                "class B important",
+                "linkStyle 0 stroke:#ff3",
+                "B --> C([END])",
+            ]
+        )
+    )
+
+    assert len(styled.nodes) == len(plain.nodes) == 3
+    assert sum(len(edges) for edges in styled.outgoing.values()) == 2
+    assert styled == plain
+
+
+def test_parse_flowchart_ignores_click_lines() -> None:
+    lines = [
+        "flowchart TD",
+        "A([BEGIN]) --> B[Read docs]",
+        "B --> C([END])",
+    ]
+    with_clicks = [
+        *lines,
+        'click B "https://example.com/docs" "Open docs"',
+        'click C href "https://example.com/end"',
+    ]
+
+    assert parse_mermaid_flowchart("\n".join(with_clicks)) == parse_mermaid_flowchart(
+        "\n".join(lines)
+    )
+
+
+def test_parse_diagram_skips_init_directives() -> None:
+    edges = ["A([BEGIN]) --> B[Work]", "B --> C([END])"]
+    plain = parse_mermaid_flowchart("\n".join(["flowchart TD", *edges]))
+    inline = parse_mermaid_diagram(
+        "\n".join(["%%{init: {'theme': 'forest'}}%%", "flowchart TD", *edges])
+    )
+    multiline = parse_mermaid_diagram(
+        "\n".join(
+            ["%%{", "  init: {", "    'theme': 'dark'", "  }", "}%%", "flowchart TD", *edges]
+        )
+    )
+    state = parse_mermaid_diagram(
+        "\n".join(
+            ["%%{init: {", "'theme': 'dark'}}%%", "stateDiagram-v2", "[*] --> A", "A --> [*]"]
+        )
+    )
+
+    assert inline == plain
+    assert multiline == plain
+    assert multiline.begin_id == "A"
+    assert state.nodes[state.begin_id].kind == "begin"
+
+
+def test_parse_flowchart_left_to_right() -> None:
+    edges = ["A([BEGIN]) --> B[Work]", "B --> C([END])"]
+    lr = parse_mermaid_flowchart("\n".join(["flowchart LR", *edges]))
+    td = parse_mermaid_flowchart("\n".join(["flowchart TD", *edges]))
+
+    assert lr.direction == "LR"
+    assert td.direction == "TD"
+    assert _flow_snapshot(lr) == _flow_snapshot(td)
+
+    with pytest.raises(FlowParseError):
+        parse_mermaid_flowchart("\n".join(["flowchart XY", *edges]))
+
+
+def test_parse_flowchart_edge_weights() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B{Enough?}",
+                "B -->|1: yes| C([END])",
+                "B -->|no| A",
+            ]
+        )
+    )
+
+    assert flow.outgoing["B"] == [
+        FlowEdge(src="B", dst="C", label="yes", weight=1),
+        FlowEdge(src="B", dst="A", label="no"),
+    ]
+
+
+def test_flow_to_mermaid_round_trip() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "graph LR",
+                "A([BEGIN]) --> B[Search stdrc]",
+                'B --> C{"Enough \\"data\\"?"}',
+                "C -->|1: yes| D([END])",
+                'C -->|"no | retry"| B',
+                "Z[Orphan]",
+            ]
+        )
+    )
+
+    text = flow.to_mermaid()
+
+    assert parse_mermaid_flowchart(text) == flow
+    assert parse_mermaid_flowchart(text).to_mermaid() == text
+    assert '    C -->|"no | retry"| B' in text.splitlines()
+
+
+def test_parse_flowchart_click_links() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Lint]",
+                "A --> B[Test]",
+                "B --> C[Ship]",
+                "C --> END([END])",
+                'click A "https://docs.example.com/lint"',
+                'click B href "https://docs.example.com/test" "Tests" _blank',
+                'click C callback "Tooltip"',
+                'click Z "https://docs.example.com/missing"',
+            ]
+        )
+    )
+
+    assert flow.nodes["A"].link == "https://docs.example.com/lint"
+    assert flow.nodes["B"].link == "https://docs.example.com/test"
+    assert flow.nodes["C"].link is None
+    assert list(flow.nodes) == ["BEGIN", "A", "B", "C", "END"]
+    reparsed = parse_mermaid_flowchart(flow.to_mermaid())
+    assert {node.id: node.link for node in reparsed.nodes.values()} == {
+        node.id: node.link for node in flow.nodes.values()
+    }
+
+
+def test_flow_to_json_round_trip() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B[Search stdrc]",
+                "B --> C{Enough?}",
+                "C -->|yes| D([END])",
+                "C -->|no| B",
+            ]
+        )
+    )
+
+    data = json.loads(json.dumps(flow.to_json()))
+
+    assert data["begin_id"] == "A"
+    assert data["end_ids"] == ["D"]
+    assert {"id": "C", "label": "Enough?", "kind": "decision"} in data["nodes"]
+    assert {"source": "C", "target": "D", "label": "yes", "weight": None} in data["edges"]
+    assert {"source": "A", "target": "B", "label": None, "weight": None} in data["edges"]
+    assert len(data["edges"]) == 4
+
+
+def test_flow_to_dot() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "A([BEGIN]) --> B{Enough?}",
+                "B -->|yes| C([END])",
+                "B -->|no| D[Retry]",
+                "D --> B",
+            ]
+        )
+    )
+
+    dot = flow.to_dot()
+
+    assert dot.startswith("digraph flow {\n")
+    assert dot.endswith("\n}")
+    assert '    "B" [label="Enough?", shape=diamond];' in dot
+    assert '    "A" [label="BEGIN", shape=oval];' in dot
+    assert '    "B" -> "C" [label="yes"];' in dot
+    assert '    "A" -> "B";' in dot
+
+
 def test_parse_choice_last_match() -> None:
     assert parse_choice("Answer <choice>a</choice> <choice>b</choice>") == "b"
     assert parse_choice("No choice tag") is None
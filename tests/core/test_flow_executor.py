@@ -0,0 +1,817 @@
+from __future__ import annotations
+
+import asyncio
+from dataclasses import replace
+
+import pytest
+from kaos.path import KaosPath
+from pydantic import BaseModel
+
+from kimi_cli.skill import Skill, find_skill_by_name, parse_skill_text
+from kimi_cli.skill.flow import Flow, FlowEdge, FlowNode
+from kimi_cli.skill.flow.executor import (
+    ErrorBranch,
+    FlowBindingError,
+    FlowContext,
+    FlowExecutionError,
+    FlowExecutor,
+    FlowLoopLimitError,
+    FlowNodeTimeoutError,
+    RetryPolicy,
+    StepOutcome,
+    TraceEntry,
+)
+from kimi_cli.skill.flow.mermaid import parse_mermaid_flowchart
+
+
+def _linear_flow() -> Flow:
+    return parse_mermaid_flowchart("flowchart TD\nBEGIN([BEGIN]) --> A[Work]\nA --> END([END])")
+
+
+@pytest.mark.asyncio
+async def test_executor_steps_to_end() -> None:
+    executor = FlowExecutor(_linear_flow())
+
+    assert executor.current_id == "BEGIN"
+    assert executor.visited == []
+    assert await executor.step() == StepOutcome(kind="advanced", node_id="A")
+    assert executor.current_id == "A"
+    assert executor.visited == ["BEGIN", "A"]
+    assert await executor.step() == StepOutcome(kind="done", node_id="END")
+    assert await executor.step() == StepOutcome(kind="done", node_id="END")
+    assert executor.current_id == "END"
+    assert executor.visited == ["BEGIN", "A", "END"]
+
+
+def _decision_flow() -> Flow:
+    return parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A{Ok?}",
+                "A -->|yes| END([END])",
+                "A -->|no| BEGIN",
+            ]
+        )
+    )
+
+
+@pytest.mark.asyncio
+async def test_executor_needs_decision_without_resolver() -> None:
+    executor = FlowExecutor(_decision_flow())
+    await executor.step()
+
+    assert await executor.step() == StepOutcome(kind="needs_decision", node_id="A")
+    assert executor.current_id == "A"
+
+
+@pytest.mark.asyncio
+async def test_executor_branch_resolver_picks_branch() -> None:
+    executor = FlowExecutor(_decision_flow())
+    seen: list[tuple[str, list[str | None]]] = []
+
+    def resolver(node: FlowNode, edges: list[FlowEdge]) -> str:
+        seen.append((node.id, [edge.label for edge in edges]))
+        return "yes"
+
+    executor.set_branch_resolver(resolver)
+    await executor.step()
+
+    assert await executor.step() == StepOutcome(kind="done", node_id="END")
+    assert seen == [("A", ["yes", "no"])]
+
+    executor = FlowExecutor(_decision_flow())
+    executor.set_branch_resolver(lambda node, edges: "maybe")
+    await executor.step()
+    with pytest.raises(FlowExecutionError):
+        await executor.step()
+
+
+@pytest.mark.asyncio
+async def test_executor_runs_node_handler_once() -> None:
+    executor = FlowExecutor(_linear_flow())
+    entered: list[str] = []
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    executor.on_node("A", handler)
+    while (await executor.step()).kind != "done":
+        pass
+    await executor.step()
+
+    assert entered == ["A"]
+
+
+@pytest.mark.asyncio
+async def test_executor_run_awaits_async_handlers() -> None:
+    executor = FlowExecutor(_linear_flow())
+    finished: list[str] = []
+
+    async def slow(context: FlowContext) -> None:
+        await asyncio.sleep(0.01)
+        finished.append(context.node.id)
+
+    executor.on_node("A", slow)
+    executor.on_node("END", slow)
+    context = await executor.run()
+
+    assert finished == ["A", "END"]
+    assert context.node.id == "END"
+    assert context.outcome == StepOutcome(kind="done", node_id="END")
+    assert executor.current_id == "END"
+
+
+@pytest.mark.asyncio
+async def test_executor_run_with_shares_bindings() -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN([BEGIN]) --> A[Write]\nA --> B[Read]\nB --> END([END])"
+    )
+    executor = FlowExecutor(flow)
+    read: list[object] = []
+
+    async def write(context: FlowContext) -> None:
+        context.bindings["answer"] = 42
+
+    async def read_back(context: FlowContext) -> None:
+        read.append(context.bindings["answer"])
+
+    executor.on_node("A", write)
+    executor.on_node("B", read_back)
+    context = FlowContext(flow=flow, node=flow.nodes[flow.begin_id], bindings={"seed": 1})
+    final = await executor.run_with(context)
+
+    assert read == [42]
+    assert final is context
+    assert final.node.id == "END"
+    assert final.bindings == {"seed": 1, "answer": 42}
+
+
+def test_executor_dry_run_lists_path_without_handlers() -> None:
+    executor = FlowExecutor(_decision_flow())
+    entered: list[str] = []
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    executor.on_node("A", handler)
+    with pytest.raises(FlowExecutionError):
+        executor.dry_run()
+
+    executor.set_branch_resolver(lambda node, edges: "yes")
+    assert executor.dry_run() == ["BEGIN", "A", "END"]
+    assert entered == []
+    assert executor.current_id == "BEGIN"
+
+    executor.set_branch_resolver(lambda node, edges: "no")
+    with pytest.raises(FlowLoopLimitError):
+        executor.dry_run()
+    executor.set_max_steps(5)
+    with pytest.raises(FlowExecutionError, match="within 5 steps"):
+        executor.dry_run()
+
+
+@pytest.mark.asyncio
+async def test_executor_dry_run_keeps_pending_error_branch() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Work]",
+                "A -->|ok| END([END])",
+                "A -->|error| B[Recover]",
+                "B --> END",
+            ]
+        )
+    )
+    executor = FlowExecutor(flow)
+
+    async def fail(context: FlowContext) -> None:
+        raise RuntimeError("boom")
+
+    executor.on_node("A", fail)
+    executor.set_error_policy("A", ErrorBranch("error"))
+    executor.set_branch_resolver(lambda node, edges: "ok")
+    await executor.step()
+
+    assert executor.current_id == "A"
+    assert executor.dry_run() == ["BEGIN", "A", "END"]
+    assert (await executor.step()).node_id == "B"
+
+
+@pytest.mark.asyncio
+async def test_executor_run_stops_when_cancelled() -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN([BEGIN]) --> A[Work]\nA --> B[More]\nB --> END([END])"
+    )
+    executor = FlowExecutor(flow)
+    cancel_event = asyncio.Event()
+    entered: list[str] = []
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+        if context.node.id == "A":
+            cancel_event.set()
+
+    for node_id in ("A", "B", "END"):
+        executor.on_node(node_id, handler)
+    context = await executor.run(cancel_event)
+
+    assert context.outcome == StepOutcome(kind="cancelled", node_id="A")
+    assert entered == ["A"]
+    assert executor.current_id == "A"
+
+
+@pytest.mark.asyncio
+async def test_executor_node_timeout() -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN([BEGIN]) --> A[Fast]\nA --> B[Slow]\nB --> END([END])"
+    )
+    executor = FlowExecutor(flow)
+    entered: list[str] = []
+
+    async def fast(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    async def slow(context: FlowContext) -> None:
+        await asyncio.sleep(10)
+
+    executor.on_node("A", fast)
+    executor.on_node("B", slow)
+    executor.set_node_timeout(0.05)
+
+    with pytest.raises(FlowNodeTimeoutError) as exc_info:
+        await executor.run()
+
+    assert exc_info.value.node_id == "B"
+    assert entered == ["A"]
+    assert executor.current_id == "B"
+    assert executor.trace[-1].outcome == "timeout"
+
+
+@pytest.mark.asyncio
+async def test_executor_resume_at_node() -> None:
+    flow = _linear_flow()
+    executor = FlowExecutor(flow)
+    entered: list[str] = []
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    executor.on_node("BEGIN", handler)
+    executor.on_node("A", handler)
+    context = FlowContext(flow=flow, node=flow.nodes["A"], bindings={"restored": True})
+
+    with pytest.raises(FlowExecutionError):
+        await executor.resume_at("Z", context)
+    final = await executor.resume_at("A", context)
+
+    assert entered == ["A"]
+    assert final.outcome == StepOutcome(kind="done", node_id="END")
+    assert final.bindings == {"restored": True}
+
+
+@pytest.mark.asyncio
+async def test_executor_reports_transitions() -> None:
+    executor = FlowExecutor(_decision_flow())
+    transitions: list[tuple[str, str]] = []
+    answers = iter(["no", "yes"])
+
+    executor.on_transition(lambda src, dst: transitions.append((src, dst)))
+    executor.set_branch_resolver(lambda node, edges: next(answers))
+    await executor.run()
+
+    assert transitions == [
+        ("", "BEGIN"),
+        ("BEGIN", "A"),
+        ("A", "BEGIN"),
+        ("BEGIN", "A"),
+        ("A", "END"),
+    ]
+
+
+@pytest.mark.asyncio
+async def test_executor_run_stops_at_max_steps() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Retry]",
+                "A -->|again| A",
+                "A -->|done| END([END])",
+            ]
+        )
+    )
+    executor = FlowExecutor(flow)
+    executor.set_branch_resolver(lambda node, edges: "again")
+    executor.set_max_steps(5)
+
+    context = await executor.run()
+
+    assert context.outcome == StepOutcome(kind="step_limit_exceeded", node_id="A")
+    assert executor.visited == ["BEGIN", "A", "A", "A", "A", "A"]
+
+
+@pytest.mark.asyncio
+async def test_executor_loop_limit() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Retry]",
+                "A -->|again| A",
+                "A -->|done| END([END])",
+            ]
+        )
+    )
+    executor = FlowExecutor(flow)
+    executor.set_branch_resolver(lambda node, edges: "again")
+    executor.set_loop_limit(3)
+
+    with pytest.raises(FlowLoopLimitError) as exc_info:
+        await executor.run()
+
+    assert exc_info.value.node_id == "A"
+    assert executor.visited == ["BEGIN", "A", "A", "A"]
+
+
+@pytest.mark.asyncio
+async def test_executor_loop_limit_counts_each_run_separately() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Retry]",
+                "A -->|again| A",
+                "A -->|done| END([END])",
+            ]
+        )
+    )
+    executor = FlowExecutor(flow)
+    choices: list[str] = []
+    executor.set_branch_resolver(lambda node, edges: choices.pop(0))
+    executor.set_loop_limit(3)
+
+    choices[:] = ["again", "again", "done"]
+    first = await executor.run()
+    choices[:] = ["again", "again", "done"]
+    second = await executor.resume_at("BEGIN", FlowContext(flow=flow, node=flow.nodes["BEGIN"]))
+    choices[:] = ["again", "done"]
+    third = await executor.resume_at("A", FlowContext(flow=flow, node=flow.nodes["A"]))
+
+    assert first.outcome == StepOutcome(kind="done", node_id="END")
+    assert second.outcome == StepOutcome(kind="done", node_id="END")
+    assert third.outcome == StepOutcome(kind="done", node_id="END")
+
+
+@pytest.mark.asyncio
+async def test_executor_runs_fork_branches_concurrently() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Split work]",
+                "A --> B[Left half]",
+                "A --> C[Right half]",
+                "B --> D[Merge]",
+                "C --> D",
+                "D --> END([END])",
+            ]
+        )
+    )
+    executor = FlowExecutor(flow)
+    both_started = asyncio.Event()
+    started: list[str] = []
+    joined: list[object] = []
+
+    async def half(context: FlowContext) -> None:
+        started.append(context.node.id)
+        if len(started) == 2:
+            both_started.set()
+        # Only finishes if the other branch runs at the same time.
+        await asyncio.wait_for(both_started.wait(), timeout=1)
+        context.bindings[context.node.id] = True
+
+    async def merge_node(context: FlowContext) -> None:
+        joined.append(dict(context.bindings))
+
+    def merge(branches: list[dict[str, object]]) -> dict[str, object]:
+        return {key: value for bindings in branches for key, value in bindings.items()}
+
+    executor.on_node("B", half)
+    executor.on_node("C", half)
+    executor.on_node("D", merge_node)
+    executor.set_parallel(merge)
+    context = await executor.run_with(
+        FlowContext(flow=flow, node=flow.nodes["BEGIN"], bindings={"seed": 1})
+    )
+
+    assert sorted(started) == ["B", "C"]
+    assert joined == [{"seed": 1, "B": True, "C": True}]
+    assert context.outcome == StepOutcome(kind="done", node_id="END")
+    assert executor.visited.count("D") == 1
+
+
+def _labeled_fork_flow() -> Flow:
+    return parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Split work]",
+                "A -->|left| B[Left half]",
+                "A -->|right| C[Right half]",
+                "B --> D[Merge]",
+                "C --> D",
+                "D --> END([END])",
+            ]
+        )
+    )
+
+
+@pytest.mark.asyncio
+async def test_executor_parallel_forks_only_unlabeled_or_marked_nodes() -> None:
+    def merge(branches: list[dict[str, object]]) -> dict[str, object]:
+        return {}
+
+    decided = FlowExecutor(_labeled_fork_flow())
+    decided.set_parallel(merge)
+    decided.set_branch_resolver(lambda node, edges: "left")
+    await decided.run()
+
+    flow = _labeled_fork_flow()
+    flow.nodes["A"] = replace(flow.nodes["A"], metadata={"fork": True})
+    marked = FlowExecutor(flow)
+    marked.set_parallel(merge)
+    await marked.run()
+
+    assert decided.visited == ["BEGIN", "A", "B", "D", "END"]
+    assert sorted(marked.visited[2:4]) == ["B", "C"]
+    assert marked.visited[4:] == ["D", "END"]
+
+
+@pytest.mark.asyncio
+async def test_executor_parallel_branches_honor_cancel_and_step_limit() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Split work]",
+                "A --> B[Left]",
+                "A --> C[Right]",
+                "B --> B2[Left again]",
+                "B2 --> D[Merge]",
+                "C --> D",
+                "D --> END([END])",
+            ]
+        )
+    )
+    cancel_event = asyncio.Event()
+
+    async def cancel(context: FlowContext) -> None:
+        cancel_event.set()
+
+    cancelled = FlowExecutor(flow)
+    cancelled.set_parallel(lambda branches: {})
+    cancelled.on_node("B", cancel)
+    cancelled_context = await cancelled.run(cancel_event)
+
+    limited = FlowExecutor(flow)
+    limited.set_parallel(lambda branches: {})
+    limited.set_max_steps(3)
+    limited_context = await limited.run()
+
+    assert cancelled_context.outcome == StepOutcome(kind="cancelled", node_id="A")
+    assert "B2" not in cancelled.visited and "D" not in cancelled.visited
+    assert limited_context.outcome == StepOutcome(kind="step_limit_exceeded", node_id="A")
+    assert "D" not in limited.visited
+    assert limited.current_id == "A"
+
+
+@pytest.mark.asyncio
+async def test_executor_parallel_failing_branch_cancels_siblings() -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN --> A\nA --> B\nA --> C\nB --> D\nC --> D\nD --> END"
+    )
+    cancelled: list[str] = []
+
+    async def fail(context: FlowContext) -> None:
+        await asyncio.sleep(0)
+        raise RuntimeError("left half failed")
+
+    async def wait(context: FlowContext) -> None:
+        try:
+            await asyncio.sleep(10)
+        except asyncio.CancelledError:
+            cancelled.append(context.node.id)
+            raise
+
+    executor = FlowExecutor(flow)
+    executor.set_parallel(lambda branches: {})
+    executor.on_node("B", fail)
+    executor.on_node("C", wait)
+
+    with pytest.raises(RuntimeError, match="left half failed"):
+        await executor.run()
+
+    assert cancelled == ["C"]
+    assert "D" not in executor.visited
+
+
+def _nested_fork_flow() -> Flow:
+    return parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN --> A",
+                "A --> B",
+                "A --> C",
+                "B --> B1",
+                "B --> B2",
+                "B1 --> B3",
+                "B2 --> B3",
+                "B3 --> D",
+                "C --> D",
+                "D --> END",
+            ]
+        )
+    )
+
+
+@pytest.mark.asyncio
+async def test_executor_parallel_runs_nested_forks() -> None:
+    flow = _nested_fork_flow()
+    executor = FlowExecutor(flow)
+    joined: dict[str, dict[str, object]] = {}
+
+    async def mark(context: FlowContext) -> None:
+        context.bindings[context.node.id] = True
+
+    async def join(context: FlowContext) -> None:
+        joined[context.node.id] = dict(context.bindings)
+
+    def merge(branches: list[dict[str, object]]) -> dict[str, object]:
+        return {key: value for bindings in branches for key, value in bindings.items()}
+
+    for node_id in ("B1", "B2", "C"):
+        executor.on_node(node_id, mark)
+    executor.on_node("B3", join)
+    executor.on_node("D", join)
+    executor.set_parallel(merge)
+    context = await executor.run()
+
+    assert joined == {"B3": {"B1": True, "B2": True}, "D": {"B1": True, "B2": True, "C": True}}
+    assert context.outcome == StepOutcome(kind="done", node_id="END")
+    assert executor.visited.count("B3") == 1 and executor.visited.count("D") == 1
+
+
+def test_executor_dry_run_lists_parallel_branches() -> None:
+    executor = FlowExecutor(_nested_fork_flow())
+    executor.set_parallel(lambda branches: {})
+
+    assert executor.dry_run() == ["BEGIN", "A", "B", "B1", "B2", "B3", "C", "D", "END"]
+
+    executor.set_max_steps(5)
+    with pytest.raises(FlowExecutionError, match="within 5 steps"):
+        executor.dry_run()
+
+
+@pytest.mark.asyncio
+async def test_executor_error_policy() -> None:
+    flow = parse_mermaid_flowchart(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A[Deploy]",
+                "A -->|ok| B[Announce]",
+                "A -->|error| R[Roll back]",
+                "B --> END([END])",
+                "R --> END",
+            ]
+        )
+    )
+    entered: list[str] = []
+
+    async def fail(context: FlowContext) -> None:
+        raise RuntimeError("deploy failed")
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    executor = FlowExecutor(flow)
+    executor.on_node("A", fail)
+    executor.on_node("R", handler)
+    executor.set_branch_resolver(lambda node, edges: "ok")
+    with pytest.raises(RuntimeError):
+        await executor.run()
+    assert executor.current_id == "A"
+
+    executor = FlowExecutor(flow)
+    executor.on_node("A", fail)
+    executor.on_node("R", handler)
+    executor.set_branch_resolver(lambda node, edges: "ok")
+    executor.set_error_policy("A", ErrorBranch("error"))
+    context = await executor.run()
+
+    assert entered == ["R"]
+    assert executor.visited == ["BEGIN", "A", "R", "END"]
+    assert context.outcome == StepOutcome(kind="done", node_id="END")
+
+    executor.set_error_policy("A", "continue")
+    with pytest.raises(FlowExecutionError):
+        executor.set_error_policy("A", ErrorBranch("retry"))
+
+
+@pytest.mark.asyncio
+async def test_executor_records_trace() -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN([BEGIN]) --> A[Work]\nA --> B[Flaky]\nB --> END([END])"
+    )
+    executor = FlowExecutor(flow)
+
+    async def slow(context: FlowContext) -> None:
+        await asyncio.sleep(0.02)
+
+    async def fail(context: FlowContext) -> None:
+        raise RuntimeError("flaky")
+
+    executor.on_node("A", slow)
+    executor.on_node("B", fail)
+    executor.set_error_policy("B", "continue")
+    await executor.run()
+    trace = executor.trace
+
+    assert [entry.node_id for entry in trace] == executor.visited
+    assert [entry.outcome for entry in trace] == ["ok", "ok", "err", "ok"]
+    assert trace[1].duration >= 0.02
+    assert all(isinstance(entry, TraceEntry) and entry.duration >= 0 for entry in trace)
+    assert [entry.entered_at for entry in trace] == sorted(entry.entered_at for entry in trace)
+
+
+@pytest.mark.asyncio
+async def test_executor_highlights_taken_path() -> None:
+    flow = _decision_flow()
+    executor = FlowExecutor(flow)
+    executor.set_branch_resolver(lambda node, edges: "yes")
+    assert executor.to_highlighted_mermaid() == flow.to_mermaid()
+
+    await executor.run()
+    lines = executor.to_highlighted_mermaid().splitlines()
+
+    assert lines[: len(flow.to_mermaid().splitlines())] == flow.to_mermaid().splitlines()
+    assert "    style A fill:#fde68a,stroke:#d97706,stroke-width:2px" in lines
+    assert [line.split()[1] for line in lines if line.lstrip().startswith("style ")] == [
+        "BEGIN",
+        "A",
+        "END",
+    ]
+    assert lines[-1] == "    linkStyle 0,1 stroke:#d97706,stroke-width:2px"
+    assert parse_mermaid_flowchart("\n".join(lines)) == flow
+
+
+@pytest.mark.asyncio
+async def test_executor_manual_stepping_with_resolver() -> None:
+    executor = FlowExecutor(_decision_flow())
+    answers = iter(["no", "yes"])
+    executor.set_branch_resolver(lambda node, edges: next(answers))
+
+    async def count(context: FlowContext) -> None:
+        context.bindings["attempts"] = context.bindings.get("attempts", 0) + 1
+
+    executor.on_node("A", count)
+    outcomes: list[StepOutcome] = []
+    while not outcomes or outcomes[-1].kind != "done":
+        outcomes.append(await executor.step())
+        assert executor.context.node.id == executor.current_id
+
+    assert [outcome.node_id for outcome in outcomes] == ["A", "BEGIN", "A", "END"]
+    assert executor.context.bindings == {"attempts": 2}
+
+
+class _Review(BaseModel):
+    path: str
+    issues: list[str]
+
+
+def test_flow_context_typed_bindings() -> None:
+    flow = _linear_flow()
+    context = FlowContext(flow=flow, node=flow.nodes["A"])
+
+    context.set_typed("review", _Review(path="src/lib.rs", issues=["unused import"]))
+
+    assert context.bindings["review"] == {"path": "src/lib.rs", "issues": ["unused import"]}
+    assert context.get_as("review", _Review) == _Review(
+        path="src/lib.rs", issues=["unused import"]
+    )
+    with pytest.raises(FlowBindingError) as exc_info:
+        context.get_as("review", int)
+    assert exc_info.value.key == "review"
+    with pytest.raises(KeyError):
+        context.get_as("missing", _Review)
+
+
+def _flow_skill(name: str, diagram: str, nodes: str = "") -> Skill:
+    return parse_skill_text(
+        f"---\nname: {name}\ntype: flow\n{nodes}---\n```mermaid\nflowchart TD\n{diagram}\n```\n",
+        dir_path=KaosPath(name),
+    )
+
+
+@pytest.mark.asyncio
+async def test_executor_runs_subflow_skills() -> None:
+    skills = [
+        _flow_skill(
+            "release",
+            "BEGIN([BEGIN]) --> A[Review]\nA --> B[Ship]\nB --> END([END])",
+            "nodes:\n  A: { subflow: review }\n",
+        ),
+        _flow_skill("review", "BEGIN([BEGIN]) --> R[Check]\nR --> END([END])"),
+        _flow_skill(
+            "again",
+            "BEGIN([BEGIN]) --> L[Loop]\nL --> END([END])",
+            "nodes:\n  L: { subflow: again }\n",
+        ),
+    ]
+    flows = {skill.name: skill.flow for skill in skills}
+    entered: list[str] = []
+
+    async def handler(context: FlowContext) -> None:
+        entered.append(context.node.id)
+        context.bindings[context.node.id] = True
+
+    def resolve(name: str) -> Flow | None:
+        skill = find_skill_by_name(skills, name)
+        return skill.flow if skill is not None else None
+
+    assert flows["release"] is not None
+    executor = FlowExecutor(flows["release"], name="release")
+    executor.set_subflow_resolver(resolve)
+    executor.on_node("A", handler)
+    executor.on_node("B", handler)
+    executor.on_subflow_node("review", "R", handler)
+    context = await executor.run()
+
+    assert entered == ["A", "R", "B"]
+    assert context.bindings == {"A": True, "R": True, "B": True}
+
+    assert flows["again"] is not None
+    executor = FlowExecutor(flows["again"], name="again")
+    executor.set_subflow_resolver(resolve)
+    with pytest.raises(FlowExecutionError, match="Sub-flow cycle: again -> again"):
+        await executor.run()
+
+
+@pytest.mark.asyncio
+async def test_executor_cancel_stops_running_subflow() -> None:
+    release = _flow_skill(
+        "release",
+        "BEGIN([BEGIN]) --> A[Review]\nA --> B[Ship]\nB --> END([END])",
+        "nodes:\n  A: { subflow: review }\n",
+    )
+    review = _flow_skill(
+        "review", "BEGIN([BEGIN]) --> R[Check]\nR --> S[Sign off]\nS --> END([END])"
+    )
+    assert release.flow is not None and review.flow is not None
+    cancel_event = asyncio.Event()
+    entered: list[str] = []
+
+    async def check(context: FlowContext) -> None:
+        entered.append(context.node.id)
+        context.bindings["checked"] = True
+        cancel_event.set()
+
+    async def record(context: FlowContext) -> None:
+        entered.append(context.node.id)
+
+    executor = FlowExecutor(release.flow, name="release")
+    executor.set_subflow_resolver(lambda name: review.flow if name == "review" else None)
+    executor.on_subflow_node("review", "R", check)
+    executor.on_subflow_node("review", "S", record)
+    executor.on_node("B", record)
+    context = await executor.run(cancel_event)
+
+    assert entered == ["R"]
+    assert context.outcome == StepOutcome(kind="cancelled", node_id="A")
+    assert "checked" not in context.bindings
+
+
+@pytest.mark.asyncio
+async def test_executor_retries_failing_handler() -> None:
+    executor = FlowExecutor(_linear_flow())
+    calls: list[int] = []
+
+    async def flaky(context: FlowContext) -> None:
+        calls.append(len(calls) + 1)
+        if len(calls) == 1:
+            raise ConnectionError("reset by peer")
+        context.bindings["fetched"] = True
+
+    executor.on_node("A", flaky)
+    executor.set_retry("A", RetryPolicy(attempts=3, backoff=0.01))
+    context = await executor.run()
+
+    assert calls == [1, 2]
+    assert context.bindings == {"fetched": True}
+    assert context.outcome == StepOutcome(kind="done", node_id="END")
+    assert [entry.outcome for entry in executor.trace] == ["ok", "ok", "ok"]
+    with pytest.raises(ValueError):
+        executor.set_retry("A", RetryPolicy(attempts=0))
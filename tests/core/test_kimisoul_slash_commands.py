@@ -7,9 +7,10 @@ from kosong.tooling.empty import EmptyToolset
 
 from kimi_cli.skill import Skill
 from kimi_cli.skill.flow import Flow, FlowEdge, FlowNode
+from kimi_cli.skill.flow.mermaid import parse_mermaid_flowchart
 from kimi_cli.soul.agent import Agent, Runtime
 from kimi_cli.soul.context import Context
-from kimi_cli.soul.kimisoul import KimiSoul
+from kimi_cli.soul.kimisoul import FlowRunner, KimiSoul
 
 
 def _make_flow() -> Flow:
@@ -24,8 +25,7 @@ def _make_flow() -> Flow:
     return Flow(nodes=nodes, outgoing=outgoing, begin_id="BEGIN", end_id="END")
 
 
-def test_flow_skill_registers_skill_and_flow_commands(runtime: Runtime, tmp_path: Path) -> None:
-    flow = _make_flow()
+def _make_soul(runtime: Runtime, tmp_path: Path, flow: Flow) -> KimiSoul:
     skill_dir = tmp_path / "flow-skill"
     skill_dir.mkdir()
     flow_skill = Skill(
@@ -43,8 +43,25 @@ def test_flow_skill_registers_skill_and_flow_commands(runtime: Runtime, tmp_path
         toolset=EmptyToolset(),
         runtime=runtime,
     )
-    soul = KimiSoul(agent, context=Context(file_backend=tmp_path / "history.jsonl"))
+    return KimiSoul(agent, context=Context(file_backend=tmp_path / "history.jsonl"))
+
+
+def test_flow_skill_registers_skill_and_flow_commands(runtime: Runtime, tmp_path: Path) -> None:
+    soul = _make_soul(runtime, tmp_path, _make_flow())
 
     command_names = {cmd.name for cmd in soul.available_slash_commands}
     assert "skill:flow-skill" in command_names
     assert "flow:flow-skill" in command_names
+
+
+def test_flow_skill_with_fan_out_skips_flow_command(runtime: Runtime, tmp_path: Path) -> None:
+    flow = parse_mermaid_flowchart(
+        "flowchart TD\nBEGIN --> A\nA --> B\nA --> C\nB --> END\nC --> END"
+    )
+
+    soul = _make_soul(runtime, tmp_path, flow)
+
+    command_names = {cmd.name for cmd in soul.available_slash_commands}
+    assert FlowRunner.find_fork(flow) == "A"
+    assert "skill:flow-skill" in command_names
+    assert "flow:flow-skill" not in command_names
@@ -1,17 +1,57 @@
 """Tests for skill discovery and formatting behavior."""
 
+import asyncio
+import io
+import json
+import os
+import tarfile
 from pathlib import Path
 
 import pytest
 from inline_snapshot import snapshot
 from kaos.path import KaosPath
 
+import kimi_cli.skill as skill_module
+from kimi_cli.exception import (
+    SkillDependencyError,
+    SkillError,
+    SkillIOError,
+    SkillNotFoundError,
+    SkillParseError,
+)
 from kimi_cli.skill import (
+    DiscoveryObserver,
+    DiscoveryOptions,
+    FsSkillSource,
     Skill,
+    SkillDeprecation,
+    SkillSource,
+    diff_skills,
+    discover_applicable_skills,
     discover_skills,
+    discover_skills_from_archive,
     discover_skills_from_roots,
+    discover_skills_stream,
+    export_skill,
+    find_skill_by_name,
     get_builtin_skills_dir,
+    group_by_category,
+    import_claude_skill,
+    order_skills,
+    parse_flow,
+    parse_skill_text,
+    register_user_skills_candidate,
+    resolve_inheritance,
     resolve_skills_roots,
+    sort_by_recency,
+    write_discovery_report,
+)
+from kimi_cli.skill.flow import (
+    FlowDiagnostic,
+    FlowNodePolicy,
+    MissingBeginError,
+    lint_flow,
+    validate_flow,
 )
 
 
@@ -41,6 +81,7 @@ description: Alpha description
     for skill in skills:
         relative_dir = skill.dir.relative_to(root_path)
         skill.dir = base_dir / relative_dir
+        skill.modified = None
 
     assert skills == snapshot(
         [
@@ -57,6 +98,7 @@ description: Alpha description
                 type="standard",
                 dir=KaosPath.unsafe_from_local_path(Path("/path/to/beta")),
                 flow=None,
+                body="# No frontmatter",
             ),
         ]
     )
@@ -90,6 +132,153 @@ A --> END([END])
     assert skills[0].flow.begin_id == "BEGIN"
 
 
+def test_parse_skill_text_reads_node_policies():
+    skill = parse_skill_text(
+        """---
+name: resilient
+type: flow
+node_policies:
+  A: { timeout_secs: 30, retries: 2 }
+  Z: { retries: 1 }
+---
+```mermaid
+flowchart TD
+BEGIN([BEGIN]) --> A[Fetch]
+A --> END([END])
+```
+""",
+        dir_path=KaosPath("resilient"),
+    )
+
+    assert skill.flow is not None
+    assert skill.flow.node_policies == {"A": FlowNodePolicy(timeout_secs=30.0, retries=2)}
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        ['`node_policies` references unknown node "Z"']
+    )
+
+
+def test_parse_skill_text_attaches_node_metadata():
+    skill = parse_skill_text(
+        """---
+name: fetcher
+type: flow
+nodes:
+  A: { tool: fetch }
+  Z: { tool: shell }
+---
+```mermaid
+flowchart TD
+BEGIN([BEGIN]) --> A[Fetch]
+A --> END([END])
+```
+""",
+        dir_path=KaosPath("fetcher"),
+    )
+
+    assert skill.flow is not None
+    assert skill.flow.nodes["A"].metadata == {"tool": "fetch"}
+    assert skill.flow.nodes["BEGIN"].metadata == {}
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        ['`nodes` references unknown node "Z"']
+    )
+
+
+def test_parse_flow_standalone():
+    flow = parse_flow("flowchart TD\nBEGIN([BEGIN]) --> A[Work]\nA --> END([END])\nZ[Orphan]")
+
+    assert flow.begin_id == "BEGIN"
+    assert lint_flow(flow) == [
+        FlowDiagnostic(message='Flow node "Z" is not reachable from BEGIN')
+    ]
+    assert parse_flow("BEGIN -> A -> END", lang="d2").successors("A") == ["END"]
+
+    with pytest.raises(MissingBeginError):
+        parse_flow("flowchart TD\nA --> B")
+    with pytest.raises(MissingBeginError):
+        validate_flow({"A": flow.nodes["A"]}, {"A": []})
+
+
+def test_lint_flow_flags_unlabeled_decision_branches():
+    flow = parse_flow(
+        "\n".join(
+            [
+                "flowchart TD",
+                "BEGIN([BEGIN]) --> A{Ok?}",
+                "A -->|yes| F[Split]",
+                "A --> B[Fix]",
+                "A --> C[Ask]",
+                "B --> A",
+                "C --> A",
+                "F --> G[Left]",
+                "F --> H[Right]",
+                "G --> END([END])",
+                "H --> END",
+            ]
+        )
+    )
+
+    assert flow.nodes["A"].kind == "decision"
+    assert flow.nodes["F"].kind == "task"
+    assert [d.message for d in lint_flow(flow)] == snapshot(
+        ['Flow node "A" has 2 unlabeled of 3 outgoing edges']
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_decorated_flow_stays_flow(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+
+    _write_skill(
+        root / "styled",
+        """---
+name: styled
+description: Styled flow skill
+type: flow
+---
+```mermaid
+flowchart TD
+%% rendering only
+classDef important fill:#f9f
+BEGIN([BEGIN]) --> A[Hello]
+class A important
+style BEGIN fill:#bbf
+A --> END([END])
+```
+""",
+    )
+
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
+
+    assert skills[0].type == "flow"
+    assert skills[0].flow is not None
+    assert list(skills[0].flow.nodes) == ["BEGIN", "A", "END"]
+
+
+def test_parse_skill_text_flags_unreachable_flow_nodes():
+    skill = parse_skill_text(
+        """---
+name: orphaned
+type: flow
+---
+```mermaid
+flowchart TD
+BEGIN([BEGIN]) --> A[Work]
+A --> END([END])
+Z[Orphan]
+```
+""",
+        dir_path=KaosPath("orphaned"),
+    )
+
+    assert skill.type == "flow"
+    assert skill.flow is not None
+    assert skill.flow.unreachable_nodes() == ["Z"]
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        ['Flow node "Z" is not reachable from BEGIN']
+    )
+
+
 @pytest.mark.asyncio
 async def test_discover_skills_flow_parse_failure_falls_back(tmp_path):
     root = tmp_path / "skills"
@@ -114,90 +303,1227 @@ A --> B
     assert len(skills) == 1
     assert skills[0].type == "standard"
     assert skills[0].flow is None
+    assert [(d.level, d.message) for d in skills[0].diagnostics] == snapshot(
+        [("error", "Expected exactly one BEGIN node, found 0; loaded as a standard skill")]
+    )
 
 
 @pytest.mark.asyncio
-async def test_discover_skills_from_roots_prefers_later_dirs(tmp_path):
-    root = tmp_path / "root"
-    system_dir = root / "system"
-    user_dir = root / "user"
-    system_dir.mkdir(parents=True)
-    user_dir.mkdir(parents=True)
+async def test_discover_skills_respects_skillignore(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    for name in ("keep", "wip-search", "archived", "wipe"):
+        _write_skill(root / name, f"---\nname: {name}\n---\n")
+    (root / ".skillignore").write_text(
+        "# work in progress\nwip-*\n\n**/archived/\n", encoding="utf-8"
+    )
+
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
+
+    assert [skill.name for skill in skills] == ["keep", "wipe"]
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_skips_hidden_and_editor_temp_dirs(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+
+    _write_skill(root / "visible", "# Visible")
+    _write_skill(root / ".hidden", "# Hidden")
+    _write_skill(root / "foo~", "# Backup")
+    _write_skill(root / "bar.swp", "# Swap")
+
+    root_path = KaosPath.unsafe_from_local_path(root)
+    skills = await discover_skills(root_path)
+    assert [skill.name for skill in skills] == ["visible"]
+
+    skills = await discover_skills(root_path, options=DiscoveryOptions(include_hidden=True))
+    assert [skill.name for skill in skills] == [".hidden", "visible"]
+
+
+def test_skill_prompt_fragment_renders_flow_steps():
+    skill = parse_skill_text(
+        """---
+name: review
+description: Review changes
+type: flow
+---
+Walk through the review.
+
+```mermaid
+flowchart TD
+A([BEGIN]) --> B{Looks good?}
+B -->|yes| C([END])
+B -->|no| A
+```
+""",
+        dir_path=KaosPath("review"),
+    )
+
+    assert skill.prompt_fragment() == snapshot(
+        """\
+## Skill: review
+
+Review changes
+
+Walk through the review.
+
+```mermaid
+flowchart TD
+A([BEGIN]) --> B{Looks good?}
+B -->|yes| C([END])
+B -->|no| A
+```
+
+### Flow steps
+- A (begin): BEGIN -> B
+- B (decision): Looks good? -> [yes] C, [no] A
+- C (end): END\
+"""
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_warns_over_token_budget(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "lean", "---\nname: lean\ndescription: Short\n---\nBe brief.\n")
+    _write_skill(root / "bloated", "---\nname: bloated\ndescription: Long\n---\n" + "x" * 400)
+
+    bloated, lean = await discover_skills(
+        KaosPath.unsafe_from_local_path(root), options=DiscoveryOptions(token_budget=50)
+    )
+
+    assert lean.estimated_tokens() == 8
+    assert lean.fits_budget(8) and not lean.fits_budget(7)
+    assert lean.diagnostics == []
+    assert [d.message for d in bloated.diagnostics] == snapshot(
+        ["Skill takes about 107 tokens, over the budget of 50"]
+    )
+
+
+def test_parse_skill_text_raises_skill_parse_error():
+    with pytest.raises(SkillParseError) as exc_info:
+        parse_skill_text(
+            """---
+name: odd
+type: template
+---
+""",
+            dir_path=KaosPath("odd"),
+        )
+
+    assert isinstance(exc_info.value, SkillError)
+    assert isinstance(exc_info.value, ValueError)
+    assert exc_info.value.path == KaosPath("odd") / "SKILL.md"
+    assert str(exc_info.value) == f'{exc_info.value.path}: Invalid skill type "template"'
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_archive_reads_in_memory(tmp_path):
+    archive = tmp_path / "bundle.tar.gz"
+    with tarfile.open(archive, mode="w:gz") as tar:
+        for member_name, text in [
+            ("bundle/alpha/SKILL.md", "---\nname: alpha\ndescription: From archive\n---\nBody"),
+            ("bundle/beta/SKILL.md", "# Beta"),
+            ("bundle/beta/notes.md", "ignored"),
+            ("SKILL.md", "# Top-level files are not skills"),
+        ]:
+            data = text.encode("utf-8")
+            info = tarfile.TarInfo(member_name)
+            info.size = len(data)
+            tar.addfile(info, io.BytesIO(data))
+
+    archive_path = KaosPath.unsafe_from_local_path(archive)
+    skills = await discover_skills_from_archive(archive_path)
+
+    assert [(s.name, s.description, s.body) for s in skills] == [
+        ("alpha", "From archive", "Body"),
+        ("beta", "No description provided.", "# Beta"),
+    ]
+    assert skills[0].dir == archive_path / "bundle" / "alpha"
+    assert not (tmp_path / "bundle").exists()
 
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_archive_rejects_non_tarball(tmp_path):
+    archive = tmp_path / "broken.tar.gz"
+    archive.write_bytes(b"not a tarball")
+
+    with pytest.raises(SkillIOError):
+        await discover_skills_from_archive(KaosPath.unsafe_from_local_path(archive))
+
+
+@pytest.mark.asyncio
+async def test_import_claude_skill_maps_frontmatter(tmp_path):
     _write_skill(
-        system_dir / "shared",
+        tmp_path / "commit",
         """---
-name: shared
-description: System version
+name: commit
+description: Write commit messages
+allowed-tools: Read, Grep, Bash(git:*)
+model: inherit
+disable-model-invocation: true
 ---
+Summarize the staged diff.
 """,
     )
     _write_skill(
-        user_dir / "shared",
+        tmp_path / "review",
+        "---\nname: review\nallowed-tools: [Read]\nmodel: claude-sonnet\n---\n",
+    )
+
+    commit = await import_claude_skill(KaosPath.unsafe_from_local_path(tmp_path / "commit"))
+    review = await import_claude_skill(KaosPath.unsafe_from_local_path(tmp_path / "review"))
+
+    assert commit.name == "commit"
+    assert commit.description == "Write commit messages"
+    assert commit.allowed_tools == ["Read", "Grep", "Bash(git:*)"]
+    assert commit.model_hint is None
+    assert commit.body == "Summarize the staged diff."
+    assert commit.diagnostics == []
+    assert review.allowed_tools == ["Read"]
+    assert review.model_hint == "claude-sonnet"
+    with pytest.raises(SkillNotFoundError):
+        await import_claude_skill(KaosPath.unsafe_from_local_path(tmp_path / "missing"))
+
+
+@pytest.mark.asyncio
+async def test_export_skill_translates_frontmatter(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(
+        root / "commit",
         """---
-name: shared
-description: User version
+name: commit
+description: Write commit messages
+tags: [git]
+allowed_tools: [Read, Bash(git:*)]
+model: kimi-k2
+resources: [template.txt]
 ---
+Summarize the staged diff.
 """,
     )
+    (root / "commit" / "template.txt").write_text("feat: ...", encoding="utf-8")
+    [skill] = await discover_skills(KaosPath.unsafe_from_local_path(root))
+    out = tmp_path / "out"
 
-    root_path = KaosPath.unsafe_from_local_path(root)
-    skills = await discover_skills_from_roots(
+    kimi = await export_skill(
+        skill, "kimi", KaosPath.unsafe_from_local_path(out / "kimi" / "commit")
+    )
+    claude = await export_skill(skill, "claude", KaosPath.unsafe_from_local_path(out / "claude"))
+    codex = await export_skill(skill, "codex", KaosPath.unsafe_from_local_path(out / "codex"))
+
+    (out / "kimi" / "commit" / "template.txt").write_text("feat: ...", encoding="utf-8")
+    [reloaded] = await discover_skills(KaosPath.unsafe_from_local_path(out / "kimi"))
+    assert kimi == []
+    assert reloaded.content_hash() == skill.content_hash()
+    assert (out / "claude" / "SKILL.md").read_text(encoding="utf-8") == snapshot("""\
+---
+name: commit
+description: Write commit messages
+allowed-tools: Read, Bash(git:*)
+model: kimi-k2
+---
+
+Summarize the staged diff.
+""")
+    imported = await import_claude_skill(KaosPath.unsafe_from_local_path(out / "claude"))
+    assert imported.allowed_tools == ["Read", "Bash(git:*)"]
+    assert [d.message for d in claude] == snapshot(
         [
-            KaosPath.unsafe_from_local_path(system_dir),
-            KaosPath.unsafe_from_local_path(user_dir),
+            "Dropping `tags`: not supported by the claude format",
+            "Dropping `resources`: not supported by the claude format",
         ]
     )
-    base_dir = KaosPath.unsafe_from_local_path(Path("/path/to"))
-    for skill in skills:
-        relative_dir = skill.dir.relative_to(root_path)
-        skill.dir = base_dir / relative_dir
-
-    assert skills == snapshot(
+    assert [d.message for d in codex] == snapshot(
         [
-            Skill(
-                name="shared",
-                description="User version",
-                type="standard",
-                dir=KaosPath.unsafe_from_local_path(Path("/path/to/user/shared")),
-                flow=None,
-            )
+            "Dropping `tags`: not supported by the codex format",
+            "Dropping `allowed_tools`: not supported by the codex format",
+            "Dropping `model`: not supported by the codex format",
+            "Dropping `resources`: not supported by the codex format",
         ]
     )
 
 
 @pytest.mark.asyncio
-async def test_resolve_skills_roots_uses_layers(monkeypatch, tmp_path):
-    home_dir = tmp_path / "home"
-    user_dir = home_dir / ".config" / "agents" / "skills"
-    user_dir.mkdir(parents=True)
-    monkeypatch.setattr(Path, "home", lambda: home_dir)
-
-    work_dir = tmp_path / "project"
-    project_dir = work_dir / ".agents" / "skills"
-    project_dir.mkdir(parents=True)
+async def test_discover_skills_resolves_declared_resources(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(
+        root / "helper",
+        """---
+name: helper
+resources: [scripts/setup.sh, templates/pr.md]
+---
+""",
+    )
+    (root / "helper" / "scripts").mkdir()
+    (root / "helper" / "scripts" / "setup.sh").write_text("echo hi", encoding="utf-8")
 
-    roots = await resolve_skills_roots(KaosPath.unsafe_from_local_path(work_dir))
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
 
-    assert roots == [
-        KaosPath.unsafe_from_local_path(get_builtin_skills_dir()),
-        KaosPath.unsafe_from_local_path(user_dir),
-        KaosPath.unsafe_from_local_path(project_dir),
+    skill_dir = KaosPath.unsafe_from_local_path(root / "helper")
+    assert skills[0].resources == [
+        skill_dir / "scripts/setup.sh",
+        skill_dir / "templates/pr.md",
+    ]
+    assert skills[0].resource("scripts/setup.sh") == skill_dir / "scripts/setup.sh"
+    assert skills[0].resource("pr.md") == skill_dir / "templates/pr.md"
+    assert skills[0].resource("missing.txt") is None
+    assert [d.message for d in skills[0].diagnostics] == [
+        f"Declared resource {skill_dir / 'templates/pr.md'} does not exist"
     ]
 
 
-@pytest.mark.asyncio
-async def test_resolve_skills_roots_respects_override(tmp_path):
-    work_dir = tmp_path / "project"
-    override_dir = tmp_path / "override"
-    override_dir.mkdir()
+def test_parse_skill_text_rejects_resources_outside_skill_dir():
+    skill = parse_skill_text(
+        """---
+name: sneaky
+resources: [docs/guide.md, ../../etc/passwd]
+---
+""",
+        dir_path=KaosPath("skills", "sneaky"),
+    )
 
-    roots = await resolve_skills_roots(
-        KaosPath.unsafe_from_local_path(work_dir),
-        skills_dir_override=KaosPath.unsafe_from_local_path(override_dir),
+    assert skill.resources == [KaosPath("skills", "sneaky", "docs", "guide.md")]
+    assert skill.resource("../../etc/passwd") is None
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        [
+            'Ignoring resource `../../etc/passwd`: Path "../../etc/passwd" escapes "skills/sneaky"'
+        ]
     )
 
-    assert roots == [
-        KaosPath.unsafe_from_local_path(get_builtin_skills_dir()),
-        KaosPath.unsafe_from_local_path(override_dir),
-    ]
+
+def test_parse_skill_text_reads_model_hints():
+    skill = parse_skill_text(
+        """---
+name: tuned
+description: Tuned skill
+model: kimi-k2
+temperature: 0.2
+---
+""",
+        dir_path=KaosPath("tuned"),
+    )
+
+    assert skill.model_hint == "kimi-k2"
+    assert skill.temperature == 0.2
+    assert skill.diagnostics == []
+
+
+def test_parse_skill_text_drops_mistyped_frontmatter_values():
+    skill = parse_skill_text(
+        """---
+name: sloppy
+model: 42
+tags: kotlin
+merge_mode: overlay
+owner: platform-team
+---
+""",
+        dir_path=KaosPath("sloppy"),
+    )
+
+    assert skill.model_hint is None
+    assert skill.tags == []
+    assert skill.merge_mode == "replace"
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        [
+            "Ignoring invalid `model`: Input should be a valid string",
+            "Ignoring invalid `tags`: Input should be a valid list",
+            "Ignoring invalid `merge_mode`: Input should be 'replace' or 'extend'",
+        ]
+    )
+
+
+def test_parse_skill_text_expands_yaml_aliases():
+    skill = parse_skill_text(
+        """---
+name: aliased
+metadata:
+  shared_tags: &tags [rust, review, style]
+tags: *tags
+---
+""",
+        dir_path=KaosPath("aliased"),
+    )
+
+    assert skill.tags == ["rust", "review", "style"]
+    assert skill.diagnostics == []
+
+
+def test_parse_skill_text_toml_frontmatter_matches_yaml():
+    yaml_skill = parse_skill_text(
+        """---
+name: dual
+description: Works either way
+tags: [rust, review]
+temperature: 0.5
+---
+# Body
+""",
+        dir_path=KaosPath("dual"),
+    )
+    toml_skill = parse_skill_text(
+        """+++
+name = "dual"
+description = "Works either way"
+tags = ["rust", "review"]
+temperature = 0.5
++++
+# Body
+""",
+        dir_path=KaosPath("dual"),
+    )
+
+    assert toml_skill == yaml_skill
+    assert toml_skill.body == "# Body"
+
+
+def test_skill_localized_description():
+    skill = parse_skill_text(
+        """---
+name: greeter
+description: Greets the user
+description_i18n:
+  ja: ユーザーに挨拶する
+  zh: 向用户问好
+---
+""",
+        dir_path=KaosPath("greeter"),
+    )
+
+    assert skill.localized_description("ja") == "ユーザーに挨拶する"
+    assert skill.localized_description("zh-CN") == "向用户问好"
+    assert skill.localized_description("fr") == "Greets the user"
+
+
+def test_parse_skill_text_strict_rejects_unknown_keys():
+    content = """---
+name: owned
+owner: platform-team
+---
+"""
+    assert parse_skill_text(content, dir_path=KaosPath("owned")).name == "owned"
+
+    with pytest.raises(SkillParseError) as exc_info:
+        parse_skill_text(content, dir_path=KaosPath("owned"), strict=True)
+
+    assert str(exc_info.value).endswith("Unknown frontmatter keys: `owner`")
+
+
+def test_parse_skill_text_warns_on_unknown_keys():
+    content = "---\nname: typo\ndescriptoin: Formats code\nzzz: 1\n---\n"
+
+    skill = parse_skill_text(content, dir_path=KaosPath("typo"), warn_unknown_keys=True)
+
+    assert skill.description == "No description provided."
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        [
+            "Unknown frontmatter key `descriptoin`; did you mean `description`?",
+            "Unknown frontmatter key `zzz`",
+        ]
+    )
+    assert parse_skill_text(content, dir_path=KaosPath("typo")).diagnostics == []
+
+
+def test_parse_skill_text_clamps_out_of_range_temperature():
+    skill = parse_skill_text(
+        """---
+name: hot
+temperature: 3.5
+---
+""",
+        dir_path=KaosPath("hot"),
+    )
+
+    assert skill.model_hint is None
+    assert skill.temperature == 2.0
+    assert [d.message for d in skill.diagnostics] == snapshot(
+        ["Temperature 3.5 is outside [0.0, 2.0]; using 2.0"]
+    )
+
+
+def test_skill_render_terminal():
+    skill = parse_skill_text(
+        """---
+name: release
+description: Cut a release after checking that the changelog and version are in sync.
+type: flow
+tags: [ops, git]
+---
+
+```mermaid
+flowchart TD
+BEGIN([BEGIN]) --> A[Update the changelog]
+A --> B{Versions match?}
+B -->|yes| C[Tag and push]
+B -->|no| A
+C --> END([END])
+```
+""",
+        dir_path=KaosPath("release"),
+    )
+
+    assert skill.render_terminal(40, color=False) == snapshot(
+        """\
+release [flow]
+  Cut a release after checking that the
+  changelog and version are in sync.
+  Tags: ops, git
+Steps:
+  1. Update the changelog
+  2. Versions match?
+       yes -> Tag and push
+       no -> Update the changelog
+  3. Tag and push\
+"""
+    )
+    colored = skill.render_terminal(40)
+    assert "\x1b[1mrelease\x1b[0m" in colored
+    assert "\x1b[36myes\x1b[0m -> Tag and push" in colored
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_lenient_frontmatter_keeps_valid_keys(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(
+        root / "typo",
+        """---
+name: review
+description: Use this: for reviews
+tags: [python]
+---
+""",
+    )
+    root_path = KaosPath.unsafe_from_local_path(root)
+
+    assert await discover_skills(root_path) == []
+
+    skills = await discover_skills(root_path, options=DiscoveryOptions(lenient_frontmatter=True))
+    assert [(skill.name, skill.description, skill.tags) for skill in skills] == [
+        ("review", "No description provided.", ["python"])
+    ]
+    assert [d.message for d in skills[0].diagnostics] == snapshot(
+        ["Ignoring `description`: invalid frontmatter YAML"]
+    )
+
+
+def test_parse_skill_text_migrates_schema_versions():
+    v1 = parse_skill_text(
+        """---
+schema_version: 1
+name: legacy
+tags: review, python,
+---
+""",
+        dir_path=KaosPath("legacy"),
+    )
+    assert v1.tags == ["review", "python"]
+    assert v1.diagnostics == []
+
+    future = parse_skill_text(
+        """---
+schema_version: 9
+name: future
+tags: [review]
+---
+""",
+        dir_path=KaosPath("future"),
+    )
+    assert future.tags == ["review"]
+    assert [d.message for d in future.diagnostics] == snapshot(
+        ["Unknown `schema_version` 9; parsing as version 2"]
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_flags_deprecated_skills(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+
+    _write_skill(
+        root / "old-review",
+        """---
+name: old-review
+deprecated: true
+replaced_by: code-review
+---
+""",
+    )
+    _write_skill(
+        root / "old-lint",
+        """---
+name: old-lint
+deprecated: true
+replaced_by: lint-v2
+---
+""",
+    )
+    _write_skill(root / "code-review", "---\nname: code-review\n---\n")
+
+    skills = await discover_skills_from_roots([KaosPath.unsafe_from_local_path(root)])
+    by_name = {skill.name: skill for skill in skills}
+
+    assert by_name["old-review"].deprecation == SkillDeprecation(replaced_by="code-review")
+    assert [d.message for d in by_name["old-review"].diagnostics] == snapshot(
+        ['Skill is deprecated; use "code-review" instead']
+    )
+    assert [d.message for d in by_name["old-lint"].diagnostics] == snapshot(
+        [
+            'Skill is deprecated; use "lint-v2" instead',
+            '`replaced_by` names unknown skill "lint-v2"',
+        ]
+    )
+    assert by_name["code-review"].deprecation is None
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_mixes_sources(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "commit", "---\nname: commit\ndescription: From disk\n---\n")
+    _write_skill(root / "review", "---\nname: review\ndescription: From disk\n---\n")
+
+    class StoredSkills:
+        async def list_skills(self) -> list[Skill]:
+            return [Skill(name="review", description="From the database", dir=KaosPath("db"))]
+
+    root_path = KaosPath.unsafe_from_local_path(root)
+    skills = await discover_skills_from_roots([FsSkillSource(root_path), StoredSkills()])
+
+    assert isinstance(StoredSkills(), SkillSource)
+    assert [(s.name, s.description) for s in skills] == [
+        ("commit", "From disk"),
+        ("review", "From the database"),
+    ]
+    assert skills == await discover_skills_from_roots([root_path, StoredSkills()])
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_checks_invoked_skills(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "format-code", "---\nname: format-code\n---\n")
+    _write_skill(
+        root / "ship",
+        """---
+name: ship
+type: flow
+---
+
+```mermaid
+flowchart TD
+BEGIN([BEGIN]) --> A[Format @skill:format-code]
+A --> B[Lint @skill:lint]
+B --> END([END])
+```
+""",
+    )
+
+    skills = await discover_skills_from_roots([KaosPath.unsafe_from_local_path(root)])
+    ship = {skill.name: skill for skill in skills}["ship"]
+
+    assert ship.flow is not None
+    assert ship.flow.nodes["A"].invokes == "format-code"
+    assert ship.flow.nodes["BEGIN"].invokes is None
+    assert [d.message for d in ship.diagnostics] == snapshot(
+        ['Flow node "B" invokes unknown skill "lint"']
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_keeps_protected_skills(tmp_path):
+    builtin_dir = tmp_path / "builtin"
+    user_dir = tmp_path / "user"
+    builtin_dir.mkdir()
+    user_dir.mkdir()
+    _write_skill(
+        builtin_dir / "core",
+        "---\nname: core\ndescription: Builtin\nprotected: true\n---\n",
+    )
+    _write_skill(user_dir / "core", "---\nname: core\ndescription: Local copy\n---\n")
+    roots = [
+        KaosPath.unsafe_from_local_path(builtin_dir),
+        KaosPath.unsafe_from_local_path(user_dir),
+    ]
+
+    skills = await discover_skills_from_roots(roots)
+
+    assert [skill.description for skill in skills] == ["Builtin"]
+    assert [d.message for d in skills[0].diagnostics] == [
+        f"Ignoring override from {roots[1] / 'core'}: skill is protected"
+    ]
+
+    overridden = await discover_skills_from_roots(
+        roots, options=DiscoveryOptions(allow_override_protected=True)
+    )
+
+    assert [skill.description for skill in overridden] == ["Local copy"]
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_prefers_later_dirs(tmp_path):
+    root = tmp_path / "root"
+    system_dir = root / "system"
+    user_dir = root / "user"
+    system_dir.mkdir(parents=True)
+    user_dir.mkdir(parents=True)
+
+    _write_skill(
+        system_dir / "shared",
+        """---
+name: shared
+description: System version
+---
+""",
+    )
+    _write_skill(
+        user_dir / "shared",
+        """---
+name: shared
+description: User version
+---
+""",
+    )
+
+    root_path = KaosPath.unsafe_from_local_path(root)
+    skills = await discover_skills_from_roots(
+        [
+            KaosPath.unsafe_from_local_path(system_dir),
+            KaosPath.unsafe_from_local_path(user_dir),
+        ]
+    )
+    base_dir = KaosPath.unsafe_from_local_path(Path("/path/to"))
+    for skill in skills:
+        relative_dir = skill.dir.relative_to(root_path)
+        skill.dir = base_dir / relative_dir
+        skill.modified = None
+
+    assert skills == snapshot(
+        [
+            Skill(
+                name="shared",
+                description="User version",
+                type="standard",
+                dir=KaosPath.unsafe_from_local_path(Path("/path/to/user/shared")),
+                flow=None,
+            )
+        ]
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_stream_yields_each_skill(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "beta", "---\nname: beta\n---\n")
+    _write_skill(root / "alpha", "---\nname: alpha\n---\n")
+    _write_skill(root / "broken", "---\nname: [unclosed\n---\n")
+    root_path = KaosPath.unsafe_from_local_path(root)
+
+    stream = discover_skills_stream(root_path)
+    first = await anext(stream)
+    rest = [skill async for skill in stream]
+
+    assert sorted(skill.name for skill in [first, *rest]) == ["alpha", "beta"]
+    assert [s.name for s in await discover_skills(root_path)] == ["alpha", "beta"]
+    assert [s async for s in discover_skills_stream(root_path / "missing")] == []
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_accepts_alternate_definition_filenames(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    (root / "lower").mkdir()
+    (root / "lower" / "skill.md").write_text("---\nname: lower\n---\nLower", encoding="utf-8")
+    _write_skill(root / "both", "---\nname: both\n---\nPrimary")
+    (root / "both" / "README.md").write_text("---\nname: readme\n---\n", encoding="utf-8")
+    root_path = KaosPath.unsafe_from_local_path(root)
+    options = DiscoveryOptions(definition_filenames=("SKILL.md", "skill.md", "README.md"))
+
+    assert [s.name for s in await discover_skills(root_path)] == ["both"]
+    both, lower = await discover_skills(root_path, options=options)
+
+    assert lower.skill_md_file == root_path / "lower" / "skill.md"
+    assert lower.body == "Lower"
+    assert both.body == "Primary"
+    assert [d.message for d in both.diagnostics] == snapshot(
+        ["Found several definition files (SKILL.md, README.md); using SKILL.md"]
+    )
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_resolves_aliases(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "formatter", "---\nname: formatter\naliases: [fmt, Format]\n---\n")
+    _write_skill(root / "prettier", "---\nname: prettier\naliases: [fmt, pretty]\n---\n")
+    _write_skill(root / "format", "---\nname: format\n---\n")
+
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
+    formatter, prettier = skills[1], skills[2]
+
+    assert formatter.aliases == ["fmt", "Format"]
+    assert prettier.aliases == ["pretty"]
+    assert [(d.level, d.message) for d in prettier.diagnostics] == [
+        ("error", 'Alias "fmt" is also claimed by skill "formatter"; ignoring it')
+    ]
+    assert find_skill_by_name(skills, "FMT") is formatter
+    assert find_skill_by_name(skills, "pretty") is prettier
+    assert find_skill_by_name(skills, "format") is skills[0]
+    assert find_skill_by_name(skills, "lint") is None
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_derives_category_from_nesting(tmp_path):
+    root = tmp_path / "skills"
+    (root / "git").mkdir(parents=True)
+    (root / "tools" / "lint").mkdir(parents=True)
+    (root / "docs").mkdir()
+    for category in ("git", "tools", "tools/lint"):
+        (root / category / ".skillcategory").touch()
+    _write_skill(root / "git" / "commit", "---\nname: commit\n---\n")
+    _write_skill(root / "git" / "rebase", "---\nname: rebase\n---\n")
+    _write_skill(root / "tools" / "lint" / "ruff", "---\nname: ruff\n---\n")
+    _write_skill(root / "review", "---\nname: review\ncategory: quality\n---\n")
+    _write_skill(root / "notes", "---\nname: notes\n---\n")
+    # Without a marker, a directory lacking SKILL.md is skipped like before.
+    _write_skill(root / "docs" / "stray", "---\nname: stray\n---\n")
+
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
+
+    assert [(skill.name, skill.category) for skill in skills] == [
+        ("commit", "git"),
+        ("notes", None),
+        ("rebase", "git"),
+        ("review", "quality"),
+        ("ruff", "tools/lint"),
+    ]
+    groups = group_by_category(skills)
+    assert {category: [skill.name for skill in group] for category, group in groups.items()} == {
+        "": ["notes"],
+        "git": ["commit", "rebase"],
+        "quality": ["review"],
+        "tools/lint": ["ruff"],
+    }
+    assert list(groups) == ["", "git", "quality", "tools/lint"]
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_notifies_observer(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "alpha", "---\nname: alpha\n---\n")
+    _write_skill(root / "broken", "---\nname: [unclosed\n---\n")
+    _write_skill(root / ".hidden", "---\nname: hidden\n---\n")
+    (root / "notes.txt").write_text("not a skill", encoding="utf-8")
+    missing = tmp_path / "missing"
+    events: list[str] = []
+
+    class Recorder(DiscoveryObserver):
+        def root_started(self, root: KaosPath) -> None:
+            events.append(f"start {root.name}")
+
+        def skill_parsed(self, skill: Skill) -> None:
+            events.append(f"skill {skill.name}")
+
+        def dir_skipped(self, path: KaosPath) -> None:
+            events.append(f"skip {path.name}")
+
+        def root_finished(
+            self, root: KaosPath, *, elapsed: float, scanned: int, skipped: int
+        ) -> None:
+            assert elapsed >= 0
+            events.append(f"finish {root.name} {scanned}/{skipped}")
+
+    roots = [KaosPath.unsafe_from_local_path(path) for path in (root, missing)]
+    skills = await discover_skills_from_roots(roots, observer=Recorder())
+
+    assert [skill.name for skill in skills] == ["alpha"]
+    assert events[0] == "start skills"
+    assert sorted(events[1:4]) == ["skill alpha", "skip .hidden", "skip broken"]
+    assert events[4:] == ["finish skills 3/2", "start missing", "finish missing 0/0"]
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_stops_when_cancelled(tmp_path, monkeypatch):
+    first = tmp_path / "first"
+    second = tmp_path / "second"
+    first.mkdir()
+    second.mkdir()
+    _write_skill(first / "alpha", "---\nname: alpha\n---\n")
+    _write_skill(second / "beta", "---\nname: beta\n---\n")
+    roots = [KaosPath.unsafe_from_local_path(path) for path in (first, second)]
+    cancel_event = asyncio.Event()
+
+    def parse_then_cancel(*args, **kwargs):
+        cancel_event.set()
+        return parse_skill_text(*args, **kwargs)
+
+    monkeypatch.setattr(skill_module, "parse_skill_text", parse_then_cancel)
+    skills = await discover_skills_from_roots(roots, cancel_event=cancel_event)
+
+    assert [skill.name for skill in skills] == ["alpha"]
+
+
+@pytest.mark.asyncio
+async def test_write_discovery_report(tmp_path):
+    system_dir = tmp_path / "system"
+    user_dir = tmp_path / "user"
+    system_dir.mkdir()
+    user_dir.mkdir()
+    _write_skill(system_dir / "shared", "---\nname: shared\n---\n")
+    _write_skill(system_dir / "base", "---\nname: Base\ntemperature: 9.5\n---\n")
+    _write_skill(user_dir / "shared", "---\nname: shared\n---\n")
+
+    roots = [KaosPath.unsafe_from_local_path(path) for path in (system_dir, user_dir)]
+    output = tmp_path / "report.json"
+    await write_discovery_report(roots, KaosPath.unsafe_from_local_path(output))
+
+    report = json.loads(output.read_text(encoding="utf-8"))
+    assert report == {
+        "roots": [root.display_normalized() for root in roots],
+        "skills": [
+            {
+                "id": "base",
+                "name": "Base",
+                "type": "standard",
+                "root": roots[0].display_normalized(),
+                "diagnostics": [
+                    {
+                        "level": "warning",
+                        "message": "Temperature 9.5 is outside [0.0, 2.0]; using 2.0",
+                    }
+                ],
+            },
+            {
+                "id": "shared",
+                "name": "shared",
+                "type": "standard",
+                "root": roots[1].display_normalized(),
+                "diagnostics": [],
+            },
+        ],
+    }
+
+
+@pytest.mark.asyncio
+async def test_discover_skills_from_roots_extend_merges_into_base(tmp_path):
+    system_dir = tmp_path / "system"
+    project_dir = tmp_path / "project"
+    system_dir.mkdir()
+    project_dir.mkdir()
+
+    _write_skill(
+        system_dir / "shared",
+        """---
+name: shared
+description: System version
+tags: [core, git]
+---
+Base instructions.
+""",
+    )
+    _write_skill(
+        project_dir / "shared",
+        """---
+name: shared
+description: Project notes
+tags: [git, local]
+merge_mode: extend
+---
+Local notes.
+""",
+    )
+
+    skills = await discover_skills_from_roots(
+        [
+            KaosPath.unsafe_from_local_path(system_dir),
+            KaosPath.unsafe_from_local_path(project_dir),
+        ]
+    )
+
+    assert len(skills) == 1
+    assert skills[0].description == "System version"
+    assert skills[0].body == "Base instructions.\n\nLocal notes."
+    assert skills[0].tags == ["core", "git", "local"]
+    assert skills[0].dir == KaosPath.unsafe_from_local_path(system_dir / "shared")
+
+
+@pytest.mark.asyncio
+async def test_discover_applicable_skills_filters_by_requires(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    _write_skill(root / "always", "# Always applicable")
+    _write_skill(
+        root / "rust",
+        """---
+name: rust
+requires: { file_exists: Cargo.toml }
+---
+""",
+    )
+    _write_skill(
+        root / "js",
+        """---
+name: js
+requires:
+  any_of:
+    - file_exists: package.json
+    - dir_exists: node_modules
+---
+""",
+    )
+
+    work_dir = tmp_path / "project"
+    work_dir.mkdir()
+    (work_dir / "Cargo.toml").write_text("[package]", encoding="utf-8")
+
+    roots = [KaosPath.unsafe_from_local_path(root)]
+    skills = await discover_applicable_skills(roots, KaosPath.unsafe_from_local_path(work_dir))
+    assert [skill.name for skill in skills] == ["always", "rust"]
+
+    (work_dir / "node_modules").mkdir()
+    skills = await discover_applicable_skills(roots, KaosPath.unsafe_from_local_path(work_dir))
+    assert [skill.name for skill in skills] == ["always", "js", "rust"]
+
+
+def test_diff_skills_buckets_by_name():
+    def skill(name: str, description: str, dir_name: str = "old") -> Skill:
+        return Skill(name=name, description=description, dir=KaosPath(dir_name) / name)
+
+    old = [skill("keep", "Same"), skill("edit", "Before"), skill("gone", "Bye")]
+    new = [
+        skill("keep", "Same", "new"),
+        skill("edit", "After"),
+        skill("zeta", "New"),
+        skill("alpha", "New"),
+    ]
+
+    diff = diff_skills(old, new)
+
+    assert [s.name for s in diff.added] == ["alpha", "zeta"]
+    assert [s.name for s in diff.removed] == ["gone"]
+    assert [s.description for s in diff.changed] == ["After"]
+    assert diff.summary() == "added 2, changed 1, removed 1"
+    assert diff_skills(old, old).is_empty()
+
+
+@pytest.mark.asyncio
+async def test_resolve_skills_roots_uses_layers(monkeypatch, tmp_path):
+    home_dir = tmp_path / "home"
+    user_dir = home_dir / ".config" / "agents" / "skills"
+    user_dir.mkdir(parents=True)
+    monkeypatch.setattr(Path, "home", lambda: home_dir)
+
+    work_dir = tmp_path / "project"
+    project_dir = work_dir / ".agents" / "skills"
+    project_dir.mkdir(parents=True)
+
+    roots = await resolve_skills_roots(KaosPath.unsafe_from_local_path(work_dir))
+
+    assert roots == [
+        KaosPath.unsafe_from_local_path(get_builtin_skills_dir()),
+        KaosPath.unsafe_from_local_path(user_dir),
+        KaosPath.unsafe_from_local_path(project_dir),
+    ]
+
+
+@pytest.mark.asyncio
+async def test_resolve_skills_roots_uses_explicit_home_dir(monkeypatch, tmp_path):
+    home_dir = tmp_path / "home"
+    user_dir = home_dir / ".agents" / "skills"
+    user_dir.mkdir(parents=True)
+    monkeypatch.setattr(Path, "home", lambda: tmp_path / "elsewhere")
+
+    roots = await resolve_skills_roots(
+        KaosPath.unsafe_from_local_path(tmp_path / "project"),
+        home_dir=KaosPath.unsafe_from_local_path(home_dir),
+    )
+
+    assert roots == [
+        KaosPath.unsafe_from_local_path(get_builtin_skills_dir()),
+        KaosPath.unsafe_from_local_path(user_dir),
+    ]
+
+
+def test_register_user_skills_candidate_slots_in_by_priority(tmp_path):
+    home = KaosPath.unsafe_from_local_path(tmp_path / "home")
+    defaults = skill_module.get_user_skills_dir_candidates(home)
+
+    unregister_mytool = register_user_skills_candidate(".mytool/skills", 350)
+    unregister_late = register_user_skills_candidate(".late/skills", 350)
+    try:
+        # Duplicates are ignored, and their handle leaves the original registration alone.
+        register_user_skills_candidate("./.mytool/skills", 1000)()
+        register_user_skills_candidate(".kimi/skills", 1000)()
+        with pytest.raises(ValueError):
+            register_user_skills_candidate("../outside/skills", 1000)
+
+        assert skill_module.get_user_skills_dir_candidates(home) == (
+            home / ".config" / "agents" / "skills",
+            home / ".agents" / "skills",
+            home / ".mytool" / "skills",
+            home / ".late" / "skills",
+            home / ".kimi" / "skills",
+            home / ".claude" / "skills",
+            home / ".codex" / "skills",
+        )
+    finally:
+        unregister_mytool()
+        unregister_late()
+
+    unregister_late()
+    assert skill_module.get_user_skills_dir_candidates(home) == defaults
+
+
+@pytest.mark.asyncio
+async def test_resolve_skills_roots_respects_override(tmp_path):
+    work_dir = tmp_path / "project"
+    override_dir = tmp_path / "override"
+    override_dir.mkdir()
+
+    roots = await resolve_skills_roots(
+        KaosPath.unsafe_from_local_path(work_dir),
+        skills_dir_override=KaosPath.unsafe_from_local_path(override_dir),
+    )
+
+    assert roots == [
+        KaosPath.unsafe_from_local_path(get_builtin_skills_dir()),
+        KaosPath.unsafe_from_local_path(override_dir),
+    ]
+
+
+@pytest.mark.asyncio
+async def test_resolve_skills_roots_can_skip_builtins(monkeypatch, tmp_path):
+    work_dir = KaosPath.unsafe_from_local_path(tmp_path / "project")
+    override = KaosPath.unsafe_from_local_path(tmp_path / "override")
+
+    skipped = await resolve_skills_roots(
+        work_dir, skills_dir_override=override, include_builtins=False
+    )
+    monkeypatch.setenv("KIMI_DISABLE_BUILTIN_SKILLS", "1")
+    disabled = await resolve_skills_roots(work_dir, skills_dir_override=override)
+
+    assert skipped == [override]
+    assert disabled == [override]
+
+
+@pytest.mark.asyncio
+async def test_sort_by_recency_uses_definition_mtime(tmp_path):
+    root = tmp_path / "skills"
+    root.mkdir()
+    for name, mtime in [("old", 1_000_000), ("new", 2_000_000), ("also-new", 2_000_000)]:
+        _write_skill(root / name, f"---\nname: {name}\ndescription: {name}\n---\n")
+        os.utime(root / name / "SKILL.md", (mtime, mtime))
+    stored = Skill(name="stored", description="Not on disk", dir=KaosPath("db"))
+
+    skills = await discover_skills(KaosPath.unsafe_from_local_path(root))
+
+    assert skills[-1].modified is not None
+    assert skills[-1].modified.timestamp() == 1_000_000
+    assert stored.modified is None
+    skills.insert(0, stored)
+    sort_by_recency(skills)
+    assert [s.name for s in skills] == ["also-new", "new", "old", "stored"]
+
+
+def test_order_skills_puts_dependencies_first():
+    def skill(name: str, *depends_on: str) -> Skill:
+        return Skill(
+            name=name,
+            description=name,
+            dir=KaosPath(name),
+            depends_on=list(depends_on),
+        )
+
+    ordered = order_skills(
+        [
+            skill("review", "base-context", "lint"),
+            skill("lint", "base-context"),
+            skill("base-context"),
+            skill("alpha"),
+        ]
+    )
+
+    assert [s.name for s in ordered] == ["alpha", "base-context", "lint", "review"]
+
+    with pytest.raises(SkillDependencyError) as missing:
+        order_skills([skill("review", "missing")])
+    assert str(missing.value).endswith('Skill "review" depends on unknown skill "missing"')
+
+    with pytest.raises(SkillDependencyError) as cycle:
+        order_skills([skill("a", "b"), skill("b", "a"), skill("c", "a")])
+    assert str(cycle.value) == snapshot("Skill dependency cycle: a -> b -> a")
+
+
+def test_resolve_inheritance_fills_unset_fields():
+    def make(name: str, **fields) -> Skill:
+        return Skill(name=name, description=name, dir=KaosPath(name), **fields)
+
+    base = make("base", tags=["review"], model_hint="kimi-k2")
+    child = parse_skill_text(
+        "---\nname: child\nextends: base\nmodel: kimi-fast\n---\n", dir_path=KaosPath("child")
+    )
+    grandchild = make("grandchild", extends="Child", tags=["python"])
+
+    resolved = resolve_inheritance([grandchild, child, base])
+
+    assert [(s.name, s.tags, s.model_hint) for s in resolved] == [
+        ("grandchild", ["python"], "kimi-fast"),
+        ("child", ["review"], "kimi-fast"),
+        ("base", ["review"], "kimi-k2"),
+    ]
+    assert resolved[2] is base
+
+    tools = make("tools", allowed_tools=["Bash"], tags=["ops"])
+    cleared = parse_skill_text(
+        "---\nname: cleared\nextends: tools\nallowed_tools: []\n---\n",
+        dir_path=KaosPath("cleared"),
+    )
+    resolved = resolve_inheritance([cleared, tools])
+    assert (resolved[0].allowed_tools, resolved[0].tags) == ([], ["ops"])
+
+    with pytest.raises(SkillDependencyError) as exc_info:
+        resolve_inheritance([make("orphan", extends="missing")])
+    assert str(exc_info.value).endswith('Skill "orphan" extends unknown skill "missing"')
+
+    with pytest.raises(SkillDependencyError) as exc_info:
+        resolve_inheritance([make("a", extends="b"), make("b", extends="a")])
+    assert str(exc_info.value) == snapshot("Skill inheritance cycle: a -> b -> a")
+
+
+def test_skill_content_hash_ignores_location():
+    content = """---
+name: hashed
+description: Hash me
+tags: [a, b]
+resources: [scripts/run.sh]
+---
+# Body
+"""
+    here = parse_skill_text(content, dir_path=KaosPath("/one/hashed"))
+    there = parse_skill_text(content, dir_path=KaosPath("/two/hashed"))
+    edited = parse_skill_text(content.replace("# Body", "# Edited"), dir_path=here.dir)
+
+    assert here.content_hash() == there.content_hash()
+    assert here.content_hash() != edited.content_hash()
+    assert len(here.content_hash()) == 64
+
+
+def test_skill_dir_round_trips_through_json(tmp_path):
+    skill = parse_skill_text(
+        "---\nname: stored\ndescription: Persist me\nresources: [notes.md]\n---\n",
+        dir_path=KaosPath.unsafe_from_local_path(tmp_path / "stored"),
+    )
+
+    payload = json.loads(skill.model_dump_json())
+    restored = Skill.model_validate_json(json.dumps(payload))
+
+    assert payload["dir"] == str(tmp_path / "stored")
+    assert restored.dir == skill.dir
+    assert restored == skill
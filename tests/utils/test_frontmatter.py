@@ -4,7 +4,12 @@ from tempfile import TemporaryDirectory
 import pytest
 from inline_snapshot import snapshot
 
-from kimi_cli.utils.frontmatter import read_frontmatter
+from kimi_cli.utils.frontmatter import (
+    read_frontmatter,
+    render_frontmatter,
+    split_frontmatter,
+    split_frontmatter_lenient,
+)
 
 
 def test_read_frontmatter_parses_yaml():
@@ -47,3 +52,57 @@ description: oops
             read_frontmatter(path)
 
         assert str(exc_info.value) == snapshot("Invalid frontmatter YAML.")
+
+
+def test_split_frontmatter_returns_body():
+    frontmatter, body = split_frontmatter("---\nname: test-skill\n---\n\n# Body\n")
+
+    assert frontmatter == {"name": "test-skill"}
+    assert body == "\n# Body"
+
+
+def test_split_frontmatter_without_frontmatter():
+    text = "# Just a body\n"
+
+    assert split_frontmatter(text) == (None, text)
+    assert split_frontmatter("---\nname: unterminated\n") == (None, "---\nname: unterminated\n")
+
+
+def test_split_frontmatter_invalid_toml():
+    with pytest.raises(ValueError) as exc_info:
+        split_frontmatter('+++\nname = "unterminated\n+++\n')
+
+    assert str(exc_info.value) == snapshot("Invalid frontmatter TOML.")
+
+
+def test_render_frontmatter_round_trips():
+    data = {"name": "commit", "description": "Write: commit messages", "tags": ["git"]}
+
+    text = render_frontmatter(data, "\nBody text\n")
+
+    assert split_frontmatter(text) == (data, "\nBody text")
+    assert render_frontmatter({}, "Body") == "Body\n"
+
+
+def test_split_frontmatter_lenient_recovers_valid_keys():
+    text = """---
+# owned by the platform team
+name: review
+description: Use this: for reviews
+tags:
+- python
+- git
+metadata:
+  owner: "unterminated
+---
+Body
+"""
+
+    with pytest.raises(ValueError):
+        split_frontmatter(text)
+    assert split_frontmatter_lenient(text) == (
+        {"name": "review", "tags": ["python", "git"]},
+        "Body",
+        ["description", "metadata"],
+    )
+    assert split_frontmatter_lenient("---\nname: ok\n---\n") == ({"name": "ok"}, "", [])